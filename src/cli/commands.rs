@@ -3,20 +3,38 @@ use clap::{Parser, Subcommand, ValueEnum};
 use colored::Colorize;
 use indicatif::MultiProgress;
 use iroh::Watcher;
-use iroh_blobs::{ticket::BlobTicket, BlobsProtocol};
-use std::path::PathBuf;
+use iroh_blobs::ticket::BlobTicket;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 use tokio::sync::mpsc;
 use url::Url;
 
 use crate::core::{
     agent_beam::AgentBeam,
-    claude_session::{ClaudeContext, ClaudeSessionInfo, GitContext},
-    config::{BeamConfig, BeamMetadata, ConnectionMode, MAX_BEAM_SIZE},
+    agents::{self, claude::CLAUDE_SESSION_PATH, AgentAdapter, AgentKind, AgentSession, RestoredSession},
+    claude_config::{ClaudeConfigFilter, ConfigChange},
+    claude_session::{
+        convert_session, handoff, ClaudeContext, ClaudeSessionInfo, ConvertTarget, GitContext, RenderFormat,
+        RenderOptions, SessionCutoff, SessionRenderer, SessionTranscript, TouchedFile,
+    },
+    cleanup::TempDirGuard,
+    config::{BeamConfig, BeamMetadata, ClaudeConfigPolicy, ConnectionMode, SecretPolicy, MAX_BEAM_SIZE, TEMP_DIR_PREFIX},
+    control::{self, ControlProtocol, CONTROL_ALPN},
+    encryption::{self, BeamCipher},
     file_collector::FileCollector,
+    git_layout::{self, RepoLayout},
+    git_repo::GitRepo,
+    git_transfer::{self, BeamCommit, GitBundle, HistoryAudit, ThinBeam, GIT_BUNDLE_PATH},
+    ledger::{self, Direction, HistoryFilter, Ledger, LedgerRecord, Outcome},
+    manifest::{self, BeamManifest, FileState, SignedManifest},
     provider_monitor::ProviderMonitor,
+    quarantine::{Quarantine, QUARANTINE_SUFFIX},
+    receipt::SignedReceipt,
     receiver::Receiver,
+    schema::CURRENT_SCHEMA,
+    ticket::{self, AgentBeamTicket, TicketInput, TicketSummary, DEFAULT_TICKET_TTL},
 };
 use crate::test_utils::dummy::DummyWorkspace;
 
@@ -59,6 +77,51 @@ pub enum Commands {
         
         #[arg(short = 'y', long, help = "Skip confirmation prompts")]
         yes: bool,
+        
+        #[arg(long, value_enum, default_value_t = SecretPolicy::Block, help = "What to do when secrets are found in workspace files")]
+        secret_policy: SecretPolicy,
+        
+        #[arg(long, value_enum, default_value_t = ClaudeConfigPolicy::Strip, help = "How to share Claude settings and MCP config in the workspace")]
+        claude_config: ClaudeConfigPolicy,
+        
+        #[arg(long, value_name = "TURN|TIMESTAMP", help = "Only beam the session from this user turn or RFC 3339 timestamp onwards")]
+        session_since: Option<SessionCutoff>,
+        
+        #[arg(long, conflicts_with = "session_since", help = "Only beam the session from the last compaction onwards")]
+        from_last_compact: bool,
+        
+        #[arg(long, help = "Beam only the Claude session and git context, not the workspace files")]
+        session_only: bool,
+        
+        #[arg(long, conflicts_with = "session_only", help = "Beam only the files the agent modified, plus the session")]
+        agent_touched_only: bool,
+        
+        #[arg(long, help = "Generate HANDOFF.md from the session for review and attach it to the beam")]
+        handoff: bool,
+        
+        #[arg(long, help = "Bundle every git ref instead of only the checked-out branch")]
+        git_all_refs: bool,
+        
+        #[arg(long, conflicts_with_all = ["session_only", "agent_touched_only"], help = "Ship HEAD, unpushed commits and uncommitted changes for a receiver that has the repo")]
+        thin: bool,
+        
+        #[arg(long, conflicts_with_all = ["session_only", "agent_touched_only", "thin"], help = "Include local Git LFS objects for files checked out as pointers (full beams only)")]
+        include_lfs: bool,
+        
+        #[arg(long = "agent", value_enum, value_delimiter = ',', help = "Only include sessions of these agents (defaults to all detected)")]
+        agents: Vec<AgentKind>,
+        
+        #[arg(long, value_name = "DURATION", value_parser = ticket::parse_duration, help = "How long the ticket stays valid, with a unit: 90s, 30m, 12h or 7d (defaults to 24h)")]
+        expires_in: Option<Duration>,
+        
+        #[arg(long, conflicts_with = "expires_in", help = "Issue a ticket that never expires")]
+        no_expiry: bool,
+        
+        #[arg(long, help = "Name shown to the receiver in the ticket preview (defaults to $USER)")]
+        name: Option<String>,
+        
+        #[arg(long, help = "Encrypt the beam with a passphrase the receiver must enter (read from $AGENTBEAM_PASSPHRASE if set)")]
+        passphrase: bool,
     },
     
     #[command(about = "Receive a shared workspace from a ticket")]
@@ -74,12 +137,126 @@ pub enum Commands {
         
         #[arg(long, help = "Use a custom relay URL")]
         relay_url: Option<Url>,
+        
+        #[arg(long, value_name = "CHECKOUT", help = "Attach the beamed session to an existing checkout instead of extracting files")]
+        attach: Option<PathBuf>,
+        
+        #[arg(long, value_enum, value_name = "AGENT", help = "Also convert the Claude session into this agent's format")]
+        convert_session: Option<ConvertTarget>,
+        
+        #[arg(long, value_name = "CLONE_OR_MIRROR", conflicts_with = "attach", help = "Reconstruct a thin beam at --target from this clone (as a new worktree) or bare mirror (as a clone)")]
+        thin_base: Option<PathBuf>,
+        
+        #[arg(long, conflicts_with = "attach", help = "Leave git hooks, .envrc, VS Code tasks, npm lifecycle scripts and Claude Code hooks active")]
+        trust: bool,
+    },
+    
+    #[command(about = "Check a received workspace against the sender's signed manifest")]
+    Verify {
+        #[arg(help = "The received workspace", default_value = "./beamed-workspace")]
+        dir: PathBuf,
+    },
+    
+    #[command(about = "Show and verify what a ticket beams, without connecting")]
+    InspectTicket {
+        #[arg(help = "The sharing ticket from the sender")]
+        ticket: String,
+    },
+    
+    #[command(about = "List the beams sent and received on this machine")]
+    History {
+        #[command(subcommand)]
+        command: Option<HistoryCommands>,
+        
+        #[arg(long, value_enum, help = "Only beams in this direction")]
+        direction: Option<Direction>,
+        
+        #[arg(long, help = "Only beams with a peer whose NodeId or name contains this")]
+        peer: Option<String>,
+        
+        #[arg(long, help = "Only beams of this workspace")]
+        workspace: Option<String>,
+        
+        #[arg(long, value_enum, help = "Only beams with this outcome")]
+        outcome: Option<Outcome>,
+        
+        #[arg(long, value_name = "DURATION", value_parser = ticket::parse_duration, help = "Only beams from the last 30m, 12h, 7d, ...")]
+        since: Option<Duration>,
+        
+        #[arg(long, help = "Print the matching records as JSON lines")]
+        json: bool,
+    },
+    
+    #[command(about = "Restore the auto-executing files quarantined in a received workspace")]
+    Trust {
+        #[arg(help = "The received workspace", default_value = "./beamed-workspace")]
+        dir: PathBuf,
+    },
+    
+    #[command(about = "Work with Claude Code session transcripts")]
+    Session {
+        #[command(subcommand)]
+        command: SessionCommands,
     },
     
     #[command(about = "Clean up test data")]
     CleanupTest,
 }
 
+#[derive(Subcommand, Debug)]
+pub enum SessionCommands {
+    #[command(about = "Render a session to Markdown or self-contained HTML")]
+    Render {
+        #[arg(help = "Session JSONL file, received workspace directory, or sharing ticket")]
+        source: String,
+        
+        #[arg(long, value_enum, default_value_t = RenderFormat::Markdown, help = "Output format")]
+        format: RenderFormat,
+        
+        #[arg(short, long, help = "Output file (defaults to <session>.<format>, use - for stdout)")]
+        output: Option<PathBuf>,
+        
+        #[arg(long, help = "Leave out thinking blocks")]
+        no_thinking: bool,
+        
+        #[arg(long, help = "Leave out system, summary and meta entries")]
+        no_system: bool,
+        
+        #[arg(long, help = "Disable relay, use direct P2P only")]
+        no_relay: bool,
+        
+        #[arg(long, help = "Use a custom relay URL")]
+        relay_url: Option<Url>,
+    },
+    
+    #[command(about = "Convert a Claude Code session into another agent's resumable format")]
+    Convert {
+        #[arg(help = "Session JSONL file, received workspace directory, or sharing ticket")]
+        source: String,
+        
+        #[arg(long, value_enum, help = "Agent to convert the session for")]
+        to: ConvertTarget,
+        
+        #[arg(long, help = "Workspace the converted session belongs to (defaults to current directory)")]
+        workspace: Option<PathBuf>,
+        
+        #[arg(long, help = "Disable relay, use direct P2P only")]
+        no_relay: bool,
+        
+        #[arg(long, help = "Use a custom relay URL")]
+        relay_url: Option<Url>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum HistoryCommands {
+    #[command(about = "Show every record of a beam and its signed manifest")]
+    Show {
+        #[arg(help = "Collection hash, or enough of its start to be unique")]
+        hash: String,
+    },
+}
+
 impl Cli {
     pub async fn execute(self) -> Result<()> {
         match self.command {
@@ -90,6 +267,21 @@ impl Cli {
                 relay_url,
                 workspace,
                 yes,
+                secret_policy,
+                claude_config,
+                session_since,
+                from_last_compact,
+                session_only,
+                agent_touched_only,
+                handoff,
+                git_all_refs,
+                thin,
+                include_lfs,
+                agents,
+                expires_in,
+                no_expiry,
+                name,
+                passphrase,
             } => {
                 let config = BeamConfig {
                     connection_mode: if no_relay {
@@ -103,6 +295,27 @@ impl Cli {
                     warn_threshold: crate::core::config::WARN_THRESHOLD,
                     force,
                     test_mode,
+                    secret_policy,
+                    claude_config_policy: claude_config,
+                    session_cutoff: if from_last_compact {
+                        Some(SessionCutoff::LastCompact)
+                    } else {
+                        session_since
+                    },
+                    session_only,
+                    agent_touched_only,
+                    git_all_refs,
+                    thin,
+                    include_lfs,
+                    attach_handoff: handoff,
+                    agents,
+                    ticket_ttl: if no_expiry {
+                        None
+                    } else {
+                        Some(expires_in.unwrap_or(DEFAULT_TICKET_TTL))
+                    },
+                    sender_name: name.or_else(control::local_display_name),
+                    encrypt: passphrase,
                 };
                 
                 // Log the configured mode for test validation
@@ -121,6 +334,10 @@ impl Cli {
                 target,
                 no_relay,
                 relay_url,
+                attach,
+                convert_session,
+                thin_base,
+                trust,
             } => {
                 let config = BeamConfig {
                     connection_mode: if no_relay {
@@ -141,7 +358,93 @@ impl Cli {
                 };
                 tracing::info!(event = "config_mode", mode = mode_str, role = "receiver");
                 
-                receive_session(ticket, target, config).await
+                match (attach, thin_base) {
+                    (Some(checkout), _) => attach_session(ticket, checkout, config, convert_session).await,
+                    (None, Some(base)) => receive_thin_beam(ticket, base, target, config, convert_session, trust).await,
+                    (None, None) => receive_session(ticket, target, config, convert_session, trust).await,
+                }
+            }
+            
+            Commands::Session { command } => match command {
+                SessionCommands::Render {
+                    source,
+                    format,
+                    output,
+                    no_thinking,
+                    no_system,
+                    no_relay,
+                    relay_url,
+                } => {
+                    let config = BeamConfig {
+                        connection_mode: if no_relay {
+                            ConnectionMode::Direct
+                        } else if let Some(url) = relay_url {
+                            ConnectionMode::CustomRelay(url)
+                        } else {
+                            ConnectionMode::DefaultRelay
+                        },
+                        ..Default::default()
+                    };
+                    let options = RenderOptions {
+                        include_thinking: !no_thinking,
+                        include_system: !no_system,
+                    };
+                    render_session(source, format, output, options, config).await
+                }
+                
+                SessionCommands::Convert {
+                    source,
+                    to,
+                    workspace,
+                    no_relay,
+                    relay_url,
+                } => {
+                    let config = BeamConfig {
+                        connection_mode: if no_relay {
+                            ConnectionMode::Direct
+                        } else if let Some(url) = relay_url {
+                            ConnectionMode::CustomRelay(url)
+                        } else {
+                            ConnectionMode::DefaultRelay
+                        },
+                        ..Default::default()
+                    };
+                    convert_session_command(source, to, workspace, config).await
+                }
+            },
+            
+            Commands::Verify { dir } => {
+                verify_workspace(&dir)
+            }
+            
+            Commands::InspectTicket { ticket } => {
+                inspect_ticket(&ticket)
+            }
+            
+            Commands::History {
+                command,
+                direction,
+                peer,
+                workspace,
+                outcome,
+                since,
+                json,
+            } => match command {
+                Some(HistoryCommands::Show { hash }) => show_history_entry(&hash),
+                None => {
+                    let filter = HistoryFilter {
+                        direction,
+                        peer,
+                        workspace,
+                        outcome,
+                        since: since.map(|since| ticket::unix_now().saturating_sub(since.as_secs())),
+                    };
+                    show_history(&filter, json)
+                }
+            },
+            
+            Commands::Trust { dir } => {
+                trust_workspace(&dir)
             }
             
             Commands::CleanupTest => {
@@ -152,31 +455,86 @@ impl Cli {
 }
 
 async fn beam_session(config: BeamConfig, workspace_path: Option<PathBuf>, skip_confirm: bool) -> Result<()> {
-    let (workspace_dir, session_dir, _guard) = if config.test_mode {
+    let (workspace_dir, _guard) = if config.test_mode {
         println!("{} TEST MODE: Using dummy data", "⚠️".yellow());
         let dummy = DummyWorkspace::create(None)?;
         println!("✓ Generated test workspace with {} files",
             std::fs::read_dir(&dummy.workspace_dir)?.count());
 
         let workspace = dummy.workspace_dir.clone();
-        let session = dummy.session_dir.clone();
 
-        (workspace, session, Some(dummy))
+        (workspace, Some(dummy))
     } else {
         let workspace = workspace_path
             .unwrap_or_else(|| PathBuf::from("."))
             .canonicalize()?;
-        let session = PathBuf::from(".claude-code-session");
-        (workspace, session, None)
+        (workspace, None)
     };
 
     // Ensure .agentbeam-* is in .gitignore
     ensure_gitignore_has_agentbeam_pattern(&workspace_dir)?;
 
+    // Scratch space for derived files (sanitized config, trimmed session, handoff)
+    let scratch_dir = std::env::temp_dir().join(format!(
+        "{}session-{}",
+        TEMP_DIR_PREFIX,
+        hex::encode(rand::random::<[u8; 8]>())
+    ));
+    let _scratch_guard = TempDirGuard::new(scratch_dir.clone());
+
+    // Derive the key up front so a mistyped passphrase stops the beam before any work is done
+    let mut collector = FileCollector::new(workspace_dir.clone());
+    if config.encrypt {
+        let cipher = tokio::task::spawn_blocking(|| BeamCipher::new(&encryption::read_new_passphrase()?)).await??;
+        collector = collector.with_encryption(cipher, scratch_dir.join("encrypted"));
+    }
+    // Agent-touched files are only known once the session has been read
+    let mut files = if config.session_only || config.agent_touched_only {
+        Vec::new()
+    } else if config.thin {
+        // Thin beams only carry what differs from HEAD
+        ThinBeam::changed_files(&workspace_dir, collector.collect_files()?)?
+    } else {
+        collector.collect_files()?
+    };
+    
+    // Sanitize Claude config, then scan for credentials before anything is imported
+    let config_changes = ClaudeConfigFilter::apply(&mut files, config.claude_config_policy, &scratch_dir)?;
+    print_config_changes(&config_changes);
+    let mut redactions = describe_config_changes(&config_changes);
+    let mut secret_findings = apply_secret_policy(&collector, &mut files, config.secret_policy)?;
+    
+    // Committed history travels too, including versions of files left out above
+    let history_audit = if config.test_mode || config.session_only || config.agent_touched_only {
+        HistoryAudit::default()
+    } else if config.thin {
+        let withheld = collector.withheld_paths()?;
+        ThinBeam::audit(&workspace_dir, |path, content| {
+            withheld.contains(path) || ClaudeConfigFilter::would_change(path, content, config.claude_config_policy)
+        })?
+    } else {
+        let withheld = collector.withheld_paths()?;
+        GitBundle::audit(&workspace_dir, config.git_all_refs, |path, content| {
+            withheld.contains(path) || ClaudeConfigFilter::would_change(path, content, config.claude_config_policy)
+        })?
+    };
+    report_history_audit(&history_audit, config.secret_policy, skip_confirm)?;
+
     if !config.test_mode && !skip_confirm {
         println!("{} This will share:", "⚠️".yellow());
-        println!("  - Your entire workspace (respecting .gitignore/.beamignore)");
-        println!("  - Claude Code conversation history");
+        if config.session_only {
+            println!("  - Only your agent sessions and git context (no workspace files)");
+        } else if config.agent_touched_only {
+            println!("  - Only the workspace files your Claude session modified");
+        } else if config.thin {
+            println!("  - Your HEAD commit, unpushed commits and uncommitted changes (no other files)");
+        } else {
+            println!("  - Your entire workspace (respecting .gitignore/.beamignore)");
+        }
+        if !history_audit.is_empty() {
+            println!("  - Committed history including the files and findings listed above");
+        }
+        println!("  - Agent conversation history (Claude Code and other detected agents)");
         println!("  - Your IP address with the recipient");
         println!();
         print!("Continue? (y/N) ");
@@ -192,32 +550,132 @@ async fn beam_session(config: BeamConfig, workspace_path: Option<PathBuf>, skip_
     }
     
     // Detect Claude session and git context
-    let claude_context = if config.test_mode {
+    let mut claude_context = if config.test_mode {
         // In test mode, create a mock context
         ClaudeContext {
             session: None,
             git_branch: "main".to_string(),
             git_has_changes: false,
             git_remote_url: None,
+            git_head: None,
+            git_stash_count: 0,
+            git_layout: RepoLayout::default(),
         }
     } else {
         println!("Detecting Claude session...");
         ClaudeContext::detect(&workspace_dir).await?
     };
+    if !config.includes_agent(AgentKind::ClaudeCode) {
+        claude_context.session = None;
+    }
+    
+    // Sessions of other agents go through their adapters; Claude is handled by ClaudeContext
+    let other_sessions = if config.test_mode {
+        Vec::new()
+    } else {
+        detect_other_agent_sessions(&workspace_dir, &config)?
+    };
+    
+    if config.session_only && claude_context.session.is_none() && other_sessions.is_empty() {
+        anyhow::bail!("No agent session found for {}; nothing to beam with --session-only",
+            workspace_dir.display());
+    }
+    if config.agent_touched_only && claude_context.session.is_none() {
+        anyhow::bail!("No Claude session found for {}; nothing to beam with --agent-touched-only",
+            workspace_dir.display());
+    }
+    
+    // Trim the session if a cutoff was requested
+    if let Some(cutoff) = &config.session_cutoff {
+        claude_context.trim_session(cutoff, &scratch_dir)?;
+    }
+    
+    let touched_files = claude_context.agent_touched_files(&workspace_dir)?;
+    if config.agent_touched_only {
+        let touched: HashSet<&str> = touched_files.iter().map(|t| t.path.as_str()).collect();
+        files = collector
+            .collect_files()?
+            .into_iter()
+            .filter(|(name, _)| touched.contains(name.as_str()))
+            .collect();
+        let config_changes = ClaudeConfigFilter::apply(&mut files, config.claude_config_policy, &scratch_dir)?;
+        print_config_changes(&config_changes);
+        redactions.extend(describe_config_changes(&config_changes));
+        secret_findings.extend(apply_secret_policy(&collector, &mut files, config.secret_policy)?);
+    }
+    
+    // Thin beams carry the git delta instead of the tree
+    let thin_beam = if config.thin {
+        // The changed files were filtered and scanned above; the patch is built from what is left
+        let thin = ThinBeam::capture(&workspace_dir, &scratch_dir, &files)?;
+        
+        let info = &thin.info;
+        println!("🪶 Thin beam at {} on {}", info.head, info.branch.as_deref().unwrap_or("detached HEAD"));
+        if let Some(ref upstream) = info.upstream {
+            println!("   Upstream: {} ({}), {} ahead, {} behind", upstream.remote, upstream.merge, info.ahead, info.behind);
+        }
+        println!("   Unpushed commits: {}", info.unpushed_commits);
+        println!("   Uncommitted changes: {}", if info.has_patch { "yes" } else { "none" });
+        files = thin.files();
+        Some(thin.info)
+    } else {
+        None
+    };
+    
+    let workspace_name = workspace_dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("workspace")
+        .to_string();
     
     // Show Claude session info if found
     if let Some(ref session) = claude_context.session {
-        println!("📎 Found Claude Code session ({} entries)", session.entry_count);
+        if session.trimmed_entry_count > 0 {
+            println!("📎 Found Claude Code session ({} entries, {} earlier entries trimmed)",
+                session.entry_count, session.trimmed_entry_count);
+        } else {
+            println!("📎 Found Claude Code session ({} entries)", session.entry_count);
+        }
+        session.stats.print_summary();
+        for problem in session.validation.problems() {
+            println!("   ⚠️  Session {}", problem);
+        }
         println!("   Branch: {}", claude_context.git_branch);
+        if let Some(ref head) = claude_context.git_head {
+            println!("   HEAD: {}", head);
+        }
         if claude_context.git_has_changes {
             println!("   ⚠️  Uncommitted changes present");
         }
+        if claude_context.git_stash_count > 0 {
+            println!("   ⚠️  {} stash entries will not be beamed", claude_context.git_stash_count);
+        }
+        print_touched_files(&touched_files, &workspace_dir);
+    }
+    
+    print_repo_layout(&claude_context.git_layout);
+    
+    for (adapter, session) in &other_sessions {
+        println!("📎 Found {} session ({} {})", session.agent, session.entry_count, adapter.entry_unit());
+    }
+    
+    // Generate the handoff so the sender can review it before consenting
+    if config.attach_handoff
+        && let Some(handoff_file) = claude_context.generate_handoff(&workspace_name, &scratch_dir)?
+    {
+        println!();
+        println!("📝 HANDOFF.md that will be attached ({}):", handoff_file.display());
+        println!();
+        for line in std::fs::read_to_string(&handoff_file)?.lines() {
+            println!("   {}", line);
+        }
     }
     
-    // Get user consent if Claude session exists
-    if !config.test_mode && !skip_confirm && claude_context.session.is_some() {
+    // Get user consent if any agent session exists
+    let has_sessions = claude_context.session.is_some() || !other_sessions.is_empty();
+    if !config.test_mode && !skip_confirm && has_sessions {
         println!();
-        println!("{} This will also share your Claude Code conversation history", "📎".cyan());
+        println!("{} This will also share your agent conversation history", "📎".cyan());
         print!("Continue with session sharing? (y/N) ");
         use std::io::{self, Write};
         io::stdout().flush()?;
@@ -234,21 +692,69 @@ async fn beam_session(config: BeamConfig, workspace_path: Option<PathBuf>, skip_
     
     let mp = MultiProgress::new();
     
-    let collector = FileCollector::new(workspace_dir.clone());
-    let mut files = collector.collect_files()?;
+    // Ship git history as a single bundle; the `.git` directory itself is never collected
+    let git_bundle = if config.session_only || config.agent_touched_only || config.thin || config.test_mode {
+        None
+    } else {
+        GitBundle::create(&workspace_dir, &scratch_dir, config.git_all_refs, history_audit.tracked.clone())?
+    };
+    if let Some(ref bundle) = git_bundle {
+        println!("📦 Bundled git history ({})",
+            if bundle.info.all_refs { "all refs" } else { bundle.info.branch.as_deref().unwrap_or("detached HEAD") });
+        files.push((GIT_BUNDLE_PATH.to_string(), bundle.path.clone()));
+    }
+    
+    // The receiver stores LFS objects in the repository it recreates from the bundle
+    if config.include_lfs {
+        if git_bundle.is_none() {
+            anyhow::bail!("--include-lfs needs the git history bundle, and this beam has none");
+        }
+        let lfs_objects = claude_context.git_layout.lfs_object_files(&workspace_dir)?;
+        if !lfs_objects.is_empty() {
+            println!("📦 Including {} LFS objects", lfs_objects.len());
+        }
+        files.extend(lfs_objects);
+    }
     
-    // Add Claude session to files if present
-    claude_context.add_to_collection(&mut files);
+    // Add agent sessions to files if present
+    let mut agent_sessions = Vec::new();
+    if let Some(info) = claude_context.add_to_collection(&mut files, &workspace_dir)? {
+        agent_sessions.push(info);
+    }
+    for (adapter, session) in &other_sessions {
+        let mut session_files = adapter.collect_files(session)?;
+        
+        // Files kept in the workspace are forced past ignore rules; give them the workspace scan
+        let is_unscanned = |name: &str, path: &Path| {
+            path.starts_with(&workspace_dir) && !files.iter().any(|(existing, _)| existing == name)
+        };
+        let mut unscanned: Vec<_> = session_files
+            .iter()
+            .filter(|(name, path)| is_unscanned(name, path))
+            .cloned()
+            .collect();
+        if !unscanned.is_empty() {
+            secret_findings.extend(apply_secret_policy(&collector, &mut unscanned, config.secret_policy)?);
+            session_files.retain(|(name, path)| {
+                !is_unscanned(name, path) || unscanned.iter().any(|(kept, _)| kept == name)
+            });
+        }
+        agent_sessions.push(session.info(&session_files, &workspace_dir));
+        
+        // Agent files may live in the workspace and already be collected
+        for (name, path) in session_files {
+            if !files.iter().any(|(existing, _)| *existing == name) {
+                files.push((name, path));
+            }
+        }
+    }
     
     println!("Packaging workspace ({} files)...", files.len());
     
     let metadata = BeamMetadata {
+        schema_version: CURRENT_SCHEMA,
         session_id: format!("session-{}", hex::encode(rand::random::<[u8; 8]>())),
-        workspace_name: workspace_dir
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("workspace")
-            .to_string(),
+        workspace_name,
         created_at: SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)?
             .as_secs(),
@@ -259,17 +765,30 @@ async fn beam_session(config: BeamConfig, workspace_path: Option<PathBuf>, skip_
             original_session_id: s.session_id.clone(),
             project_slug: s.project_slug.clone(),
             entry_count: s.entry_count,
+            trimmed_entry_count: s.trimmed_entry_count,
         }),
-        git_context: Some(GitContext {
-            branch: claude_context.git_branch.clone(),
-            has_uncommitted_changes: claude_context.git_has_changes,
-            remote_url: claude_context.git_remote_url.clone(),
-        }),
+        git_context: Some(claude_context.git_context()),
+        session_stats: claude_context.session.as_ref().map(|s| s.stats.clone()),
+        session_only: config.session_only,
+        agent_sessions,
+        agent_touched_files: touched_files,
+        agent_touched_only: config.agent_touched_only,
+        git_bundle: git_bundle.map(|bundle| bundle.info),
+        thin_beam,
+        extra: Default::default(),
     };
     
-    let (collection_tag, total_size, _collection) = collector
-        .create_collection(&agent_beam.blobs, files, metadata, Some(&mp))
+    let mut summary = TicketSummary::new(&metadata, config.ticket_ttl, config.sender_name.clone())?;
+    let sessions: Vec<String> = metadata
+        .agent_sessions
+        .iter()
+        .map(|session| format!("{} {}", session.agent, session.session_id))
+        .collect();
+    
+    let (collection_tag, total_size, manifest_json) = collector
+        .create_collection(&agent_beam.blobs, files, metadata, agent_beam.endpoint.secret_key(), Some(&mp))
         .await?;
+    summary.total_size = total_size;
     
     if total_size > config.max_size && !config.force {
         anyhow::bail!(
@@ -281,21 +800,29 @@ async fn beam_session(config: BeamConfig, workspace_path: Option<PathBuf>, skip_
     
     let (progress_tx, progress_rx) = mpsc::channel(32);
     let blobs_with_progress = agent_beam.blobs_with_progress(progress_tx);
+    let (control_tx, control_rx) = mpsc::channel(32);
+    let mut control = ControlProtocol::new(agent_beam.node_id(), *collection_tag.hash(), summary.expires_at, control_tx);
+    if config.encrypt {
+        // Older receivers would export the ciphertext without noticing
+        control = control.require("encryption");
+    }
     
     // Set up router to accept connections
     let router = iroh::protocol::Router::builder(agent_beam.endpoint.clone())
         .accept(iroh_blobs::ALPN, blobs_with_progress)
+        .accept(CONTROL_ALPN, control)
         .spawn();
     
     // Wait for endpoint to initialize
     let _ = router.endpoint().home_relay().initialized().await;
     
     let node_addr = agent_beam.node_addr().await;
-    let ticket = BlobTicket::new(
+    let blob_ticket = BlobTicket::new(
         node_addr,
         *collection_tag.hash(),
         iroh_blobs::BlobFormat::HashSeq,
     );
+    let ticket = AgentBeamTicket::new(blob_ticket, summary.clone(), agent_beam.endpoint.secret_key());
     
     // Log ticket ready for test validation
     tracing::info!(
@@ -307,41 +834,124 @@ async fn beam_session(config: BeamConfig, workspace_path: Option<PathBuf>, skip_
     println!();
     println!("Share this ticket:");
     println!("{}", ticket.to_string().bright_cyan());
+    match summary.expires_at {
+        Some(expires_at) => println!("Valid until {}", ticket::format_time(expires_at)),
+        None => println!("This ticket never expires"),
+    }
+    if config.encrypt {
+        println!("🔐 Encrypted; share the passphrase separately from the ticket");
+    }
     println!();
     
-    let mut monitor = ProviderMonitor::new(progress_rx, Some(&mp), &agent_beam.endpoint);
-    monitor.monitor_until_complete().await?;
+    let mut monitor = ProviderMonitor::new(progress_rx, control_rx, Some(&mp), &agent_beam.endpoint);
+    let expiry = async {
+        match summary.remaining() {
+            Some(remaining) => tokio::time::sleep(remaining).await,
+            None => std::future::pending().await,
+        }
+    };
+    // Why sharing stopped when it was not the monitor that ended it
+    let (delivery, stopped) = tokio::select! {
+        result = monitor.monitor_until_complete() => (result, None),
+        _ = expiry => {
+            println!("{} Ticket expired, no longer sharing", "⏱️".yellow());
+            (Ok(None), Some(Outcome::Expired))
+        }
+        _ = tokio::signal::ctrl_c() => {
+            println!("{} Cancelled, no longer sharing", "🛑".yellow());
+            (Ok(None), Some(Outcome::Cancelled))
+        }
+    };
+    
+    // Log the beam whatever happened, before a failure is propagated
+    let (outcome, detail) = match (&delivery, stopped) {
+        (Ok(Some(_)), _) => (Outcome::Delivered, None),
+        (Ok(None), Some(outcome)) => (outcome, None),
+        (Ok(None), None) => (Outcome::Failed, Some("Sharing stopped before the beam was delivered".to_string())),
+        (Err(e), _) => (Outcome::Failed, Some(format!("{:#}", e))),
+    };
+    let delivered = delivery.as_ref().ok().and_then(Option::as_ref);
+    let record = LedgerRecord {
+        timestamp: ticket::unix_now(),
+        direction: Direction::Sent,
+        collection: collection_tag.hash().to_string(),
+        workspace_name: summary.workspace_name.clone(),
+        file_count: summary.file_count,
+        total_size,
+        peer: delivered.map(|delivery| delivery.receiver.to_string()),
+        peer_name: delivered.and_then(|delivery| delivery.display_name.clone()),
+        sessions,
+        redactions,
+        secret_findings,
+        outcome,
+        detail,
+    };
+    log_beam(&record, Some(manifest_json.as_slice()));
+    
+    if let Some(receipt) = delivery?.and_then(|delivery| delivery.receipt) {
+        store_receipt(&receipt)?;
+    }
     
     agent_beam.shutdown().await?;
     
     Ok(())
 }
 
-async fn receive_session(ticket_str: String, target_dir: PathBuf, config: BeamConfig) -> Result<()> {
-    let ticket = BlobTicket::from_str(&ticket_str)
-        .context("Invalid ticket format")?;
+async fn receive_session(
+    ticket_str: String,
+    target_dir: PathBuf,
+    config: BeamConfig,
+    convert_to: Option<ConvertTarget>,
+    trust: bool,
+) -> Result<()> {
+    let (ticket, summary) = open_ticket(ticket_str.parse()?)?;
     
     let agent_beam = AgentBeam::new(config).await?;
     
     let mp = MultiProgress::new();
     
-    let receiver = Receiver::new(&agent_beam.endpoint, &agent_beam.blobs, Some(&mp));
-    receiver.receive_from_ticket(&ticket, &target_dir).await?;
+    let receiver = Receiver::new(&agent_beam.endpoint, &agent_beam.blobs, Some(&mp))
+        .with_session_target(&target_dir);
+    let result = receiver.receive_from_ticket(&ticket, &target_dir).await;
+    record_received(&ticket, summary.as_ref(), &target_dir, &result);
+    let received = result?;
     
-    let file_count = std::fs::read_dir(&target_dir)?.count();
+    // The sender's receipt names where the session was restored, so it waits for the restore
+    let restored = restore_received_workspace(&ticket, &target_dir, convert_to, trust);
+    receiver.finish(&ticket, received, restored).await?;
+    
+    agent_beam.shutdown().await?;
+    
+    Ok(())
+}
+
+/// Set up the extracted workspace: repository, sessions and safety checks
+fn restore_received_workspace(
+    ticket: &BlobTicket,
+    target_dir: &Path,
+    convert_to: Option<ConvertTarget>,
+    trust: bool,
+) -> Result<()> {
+    let file_count = std::fs::read_dir(target_dir)?.count();
     println!("{} {} files extracted", "✓".green(), file_count);
     
+    // Neutralize anything that would run on its own before a later step can fail
+    quarantine_auto_exec(target_dir, trust)?;
+    
     // Check for metadata and restore Claude session if present
     let metadata_path = target_dir.join(".agentbeam-metadata.json");
     if metadata_path.exists() {
-        let metadata_content = std::fs::read_to_string(&metadata_path)?;
-        let metadata: BeamMetadata = serde_json::from_str(&metadata_content)?;
+        let metadata = BeamMetadata::load(&metadata_path)?;
+        print_schema_notes(&metadata);
         
         // Display git context if available
         if let Some(git) = &metadata.git_context {
             println!();
             println!("📦 Git context from sender:");
             println!("   Branch: {}", git.branch);
+            if let Some(head) = &git.head_commit {
+                println!("   HEAD: {}", head);
+            }
             if git.has_uncommitted_changes {
                 println!("   ⚠️  Sender had uncommitted changes");
             }
@@ -350,84 +960,1084 @@ async fn receive_session(ticket_str: String, target_dir: PathBuf, config: BeamCo
             }
         }
         
-        // Restore Claude session if present
-        if let Some(claude_info) = &metadata.claude_session {
+        if !metadata.agent_touched_files.is_empty() {
             println!();
-            println!("📎 Restoring Claude Code session...");
-            
-            let session_source = target_dir.join(".agentbeam/claude-session.jsonl");
-            if session_source.exists() {
-                ClaudeContext::restore(&target_dir, claude_info, &session_source).await?;
-                println!("✓ Claude session restored ({} entries)", claude_info.entry_count);
-            } else {
-                println!("⚠️  Session file not found in package");
-            }
+            print_touched_files(&metadata.agent_touched_files, target_dir);
+        }
+        if metadata.agent_touched_only {
+            println!("   Only these files were beamed, not the whole workspace");
         }
         
-        // Initialize git if needed and set branch
-        if let Some(git) = &metadata.git_context {
-            if !target_dir.join(".git").exists() {
+        if metadata.thin_beam.is_some() {
+            println!();
+            println!("{} This is a thin beam; use --thin-base <clone-or-mirror> to reconstruct the workspace", "ℹ️".cyan());
+        }
+        
+        if metadata.session_only {
+            println!();
+            println!("{} This beam contains only a session; use --attach <checkout> to restore it into an existing clone", "ℹ️".cyan());
+        }
+        
+        // Restore agent sessions through their adapters
+        let restored = restore_agent_sessions(&metadata, target_dir, target_dir, true)?;
+        if let Some(convert_to) = convert_to {
+            convert_restored_session(&restored, convert_to, target_dir)?;
+        }
+        
+        // Recreate the repository from the bundle, or initialize one for older beams
+        let bundle_path = target_dir.join(GIT_BUNDLE_PATH);
+        let mut repo_created = false;
+        if let Some(bundle_info) = &metadata.git_bundle {
+            if target_dir.join(".git").exists() {
+                println!("{} {} is already a git repository; bundle left at {}",
+                    "⚠️".yellow(), target_dir.display(), bundle_path.display());
+            } else {
                 println!();
-                println!("Initializing git repository...");
-                
-                std::process::Command::new("git")
-                    .args(&["init"])
-                    .current_dir(&target_dir)
-                    .output()?;
-                
-                // Create matching branch if not main/master
-                if git.branch != "main" && git.branch != "master" {
-                    std::process::Command::new("git")
-                        .args(&["checkout", "-b", &git.branch])
-                        .current_dir(&target_dir)
-                        .output()?;
+                println!("Restoring git history from bundle...");
+                GitBundle::restore(&bundle_path, target_dir, bundle_info)?;
+                std::fs::remove_file(&bundle_path)?;
+                repo_created = true;
+                println!("✓ Git restored on {} @ {}",
+                    bundle_info.branch.as_deref().unwrap_or("detached HEAD"), bundle_info.head);
+                if let Some(upstream) = &bundle_info.upstream {
+                    println!("   Upstream: {} ({})", upstream.remote, upstream.merge);
                 }
-                
-                println!("✓ Git initialized on branch: {}", git.branch);
             }
+        } else if let Some(git) = &metadata.git_context && !target_dir.join(".git").exists() {
+            println!();
+            // A detached sender has no branch name
+            let branch = if git.branch.is_empty() { "main" } else { git.branch.as_str() };
+            init_received_repo(target_dir, branch)?;
+            repo_created = true;
+        }
+        
+        // Submodules and LFS content need the repository in place
+        if repo_created && let Some(git) = &metadata.git_context {
+            restore_repo_layout(&git.layout, target_dir)?;
+        }
+        
+        // Record exactly what was received so later local changes are a plain `git diff` away
+        if repo_created {
+            // Files restored from history and submodules have not been checked yet
+            if !trust {
+                quarantine_auto_exec(target_dir, trust)?;
+            }
+            let beam_commit = BeamCommit {
+                collection_hash: ticket.hash().to_string(),
+                sender: ticket.node_addr().node_id.to_string(),
+                sender_branch: match &metadata.git_bundle {
+                    Some(bundle) => bundle.branch.clone(),
+                    None => metadata.git_context.as_ref().map(|git| git.branch.clone()),
+                },
+                original_head: match &metadata.git_bundle {
+                    Some(bundle) => Some(bundle.head.clone()),
+                    None => metadata.git_context.as_ref().and_then(|git| git.head_commit.clone()),
+                },
+                created_at: metadata.created_at,
+            };
+            commit_received_tree(&beam_commit, target_dir)?;
         }
     }
     
+    Ok(())
+}
+
+async fn attach_session(
+    ticket_str: String,
+    checkout: PathBuf,
+    config: BeamConfig,
+    convert_to: Option<ConvertTarget>,
+) -> Result<()> {
+    let checkout = checkout
+        .canonicalize()
+        .with_context(|| format!("Checkout {} does not exist", checkout.display()))?;
+    let (ticket, summary) = open_ticket(ticket_str.parse()?)?;
+    
+    let agent_beam = AgentBeam::new(config).await?;
+    
+    let mp = MultiProgress::new();
+    
+    // Extract into a scratch directory; only the session is restored into the checkout
+    let staging_dir = std::env::temp_dir().join(format!(
+        "{}attach-{}",
+        TEMP_DIR_PREFIX,
+        hex::encode(rand::random::<[u8; 8]>())
+    ));
+    let _staging_guard = TempDirGuard::new(staging_dir.clone());
+    
+    let receiver = Receiver::new(&agent_beam.endpoint, &agent_beam.blobs, Some(&mp))
+        .with_session_target(&checkout);
+    let result = receiver.receive_from_ticket(&ticket, &staging_dir).await;
+    record_received(&ticket, summary.as_ref(), &staging_dir, &result);
+    let received = result?;
+    
+    let restored = attach_received_session(&staging_dir, &checkout, convert_to);
+    receiver.finish(&ticket, received, restored).await?;
+    
     agent_beam.shutdown().await?;
     
     Ok(())
 }
 
-fn ensure_gitignore_has_agentbeam_pattern(workspace_dir: &PathBuf) -> Result<()> {
-    let gitignore_path = workspace_dir.join(".gitignore");
-    let pattern = ".agentbeam-*";
-
-    // Read existing gitignore or create empty content
-    let mut content = if gitignore_path.exists() {
-        std::fs::read_to_string(&gitignore_path)?
-    } else {
-        String::new()
-    };
-
-    // Check if pattern already exists
-    let has_pattern = content.lines().any(|line| {
-        let trimmed = line.trim();
-        trimmed == pattern || trimmed == ".agentbeam-*/"
-    });
-
-    if !has_pattern {
-        // Add the pattern
-        if !content.is_empty() && !content.ends_with('\n') {
-            content.push('\n');
+/// Restore the sessions of a received beam into an existing checkout
+fn attach_received_session(staging_dir: &Path, checkout: &Path, convert_to: Option<ConvertTarget>) -> Result<()> {
+    let metadata = BeamMetadata::load(&staging_dir.join(".agentbeam-metadata.json"))?;
+    print_schema_notes(&metadata);
+    
+    if !metadata.session_only {
+        println!("{} Beam includes {} workspace files; they are not written to the checkout",
+            "⚠️".yellow(), metadata.file_count);
+    }
+    
+    if metadata.agent_sessions.is_empty() {
+        anyhow::bail!("Beam does not contain any agent sessions");
+    }
+    
+    // Compare the checkout with the sender's git state
+    let local_git = ClaudeContext::get_git_state(checkout)?;
+    if let Some(sender_git) = &metadata.git_context {
+        println!();
+        println!("📦 Checking {} against sender...", checkout.display());
+        check_attach_target(sender_git, &local_git, checkout)?;
+        
+        let mut diverged = false;
+        if sender_git.branch != local_git.branch {
+            diverged = true;
+            println!("   ⚠️  Branch differs: sender on {}, checkout on {}",
+                sender_git.branch, local_git.branch);
         }
-
-        // Add a comment if this is the first entry or after existing content
-        if content.is_empty() {
-            content.push_str("# AgentBeam temporary directories\n");
-        } else {
-            content.push_str("\n# AgentBeam temporary directories\n");
+        match (&sender_git.head_commit, &local_git.head_commit) {
+            (Some(sender_head), Some(local_head)) if sender_head != local_head => {
+                diverged = true;
+                println!("   ⚠️  HEAD differs: sender at {}, checkout at {}", sender_head, local_head);
+            }
+            (Some(_), None) => {
+                diverged = true;
+                println!("   ⚠️  Checkout has no commits to compare against the sender's HEAD");
+            }
+            _ => {}
         }
-        content.push_str(pattern);
-        content.push('\n');
-
-        // Write back to file
-        std::fs::write(&gitignore_path, content)?;
-        println!("✓ Added {} to .gitignore", pattern);
+        if sender_git.has_uncommitted_changes {
+            println!("   ⚠️  Sender had uncommitted changes that are not part of this beam");
+        }
+        if local_git.has_uncommitted_changes {
+            println!("   ⚠️  Checkout has uncommitted changes");
+        }
+        
+        if !diverged {
+            println!("{} Checkout matches sender on {} @ {}",
+                "✓".green(),
+                local_git.branch,
+                local_git.head_commit.as_deref().unwrap_or("(no commits)"));
+        }
+    }
+    
+    let restored = restore_agent_sessions(&metadata, staging_dir, checkout, false)?;
+    if let Some(convert_to) = convert_to {
+        convert_restored_session(&restored, convert_to, checkout)?;
+    }
+    
+    Ok(())
+}
+
+/// Refuse a checkout that is not a clone of the sender's repository
+///
+/// The origin URLs are compared when both sides have one, otherwise the checkout must already
+/// contain the sender's HEAD.
+fn check_attach_target(sender_git: &GitContext, local_git: &GitContext, checkout: &Path) -> Result<()> {
+    let Some(repo) = GitRepo::discover(checkout)? else {
+        anyhow::bail!("{} is not a git repository; --attach needs a clone of the sender's", checkout.display());
+    };
+    
+    if let (Some(sender_url), Some(local_url)) = (&sender_git.remote_url, &local_git.remote_url) {
+        if repository_key(sender_url) != repository_key(local_url) {
+            anyhow::bail!(
+                "{} is a clone of {}, but the session comes from {}",
+                checkout.display(), local_url, sender_url
+            );
+        }
+        return Ok(());
+    }
+    
+    if let Some(sender_head) = &sender_git.head_commit && !repo.has_commit(sender_head) {
+        anyhow::bail!(
+            "{} does not contain the sender's HEAD {}; fetch it first or attach to a clone of the sender's repository",
+            checkout.display(), sender_head
+        );
+    }
+    Ok(())
+}
+
+/// Host and path of a remote URL, so `https://host/org/repo` and `git@host:org/repo.git` compare equal
+fn repository_key(url: &str) -> String {
+    let url = url.trim().trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    let location = match url.split_once("://") {
+        Some((_, rest)) => rest.to_string(),
+        // scp-like `user@host:path`; a local path has no colon before its first slash
+        None => match url.split_once(':') {
+            Some((host, path)) if !host.contains('/') => format!("{}/{}", host, path),
+            _ => url.to_string(),
+        },
+    };
+    match location.split_once('/') {
+        Some((authority, path)) => {
+            let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+            format!("{}/{}", host.to_lowercase(), path)
+        }
+        None => location,
+    }
+}
+
+/// Reconstruct a thin beam from the receiver's own clone or mirror
+async fn receive_thin_beam(
+    ticket_str: String,
+    base: PathBuf,
+    target: PathBuf,
+    config: BeamConfig,
+    convert_to: Option<ConvertTarget>,
+    trust: bool,
+) -> Result<()> {
+    let base = base
+        .canonicalize()
+        .with_context(|| format!("{} does not exist", base.display()))?;
+    let (ticket, summary) = open_ticket(ticket_str.parse()?)?;
+    
+    let agent_beam = AgentBeam::new(config).await?;
+    
+    let mp = MultiProgress::new();
+    
+    let staging_dir = std::env::temp_dir().join(format!(
+        "{}thin-{}",
+        TEMP_DIR_PREFIX,
+        hex::encode(rand::random::<[u8; 8]>())
+    ));
+    let _staging_guard = TempDirGuard::new(staging_dir.clone());
+    
+    let receiver = Receiver::new(&agent_beam.endpoint, &agent_beam.blobs, Some(&mp))
+        .with_session_target(&target);
+    let package_dir = staging_dir.join("package");
+    let result = receiver.receive_from_ticket(&ticket, &package_dir).await;
+    record_received(&ticket, summary.as_ref(), &package_dir, &result);
+    let received = result?;
+    
+    let restored = reconstruct_thin_beam(&ticket, &base, &target, &package_dir, &staging_dir, convert_to, trust);
+    receiver.finish(&ticket, received, restored).await?;
+    
+    agent_beam.shutdown().await?;
+    
+    Ok(())
+}
+
+/// Rebuild the sender's tree from `base` in `target` and restore the sessions there
+fn reconstruct_thin_beam(
+    ticket: &BlobTicket,
+    base: &Path,
+    target: &Path,
+    package_dir: &Path,
+    staging_dir: &Path,
+    convert_to: Option<ConvertTarget>,
+    trust: bool,
+) -> Result<()> {
+    let metadata = BeamMetadata::load(&package_dir.join(".agentbeam-metadata.json"))?;
+    print_schema_notes(&metadata);
+    let Some(thin) = &metadata.thin_beam else {
+        anyhow::bail!("This beam is not a thin beam; receive it without --thin-base");
+    };
+    
+    // A bare mirror is cloned into the target; a working clone gets a new worktree there
+    println!();
+    if git_transfer::is_bare_repository(base) {
+        println!("🪶 Cloning {} and reconstructing {} in {}...", base.display(), thin.head, target.display());
+    } else {
+        println!("🪶 Reconstructing {} in a new worktree of {} at {}...", thin.head, base.display(), target.display());
+    }
+    ThinBeam::reconstruct(thin, package_dir, base, target, &staging_dir.join("verify"))?;
+    let repo = target.canonicalize()?;
+    
+    // The reconstructed tree is new to this machine, whatever the base already had checked out
+    quarantine_auto_exec(&repo, trust)?;
+    println!("{} Tree matches the sender ({} unpushed commits, {})",
+        "✓".green(),
+        thin.unpushed_commits,
+        if thin.has_patch { "uncommitted changes applied" } else { "no uncommitted changes" });
+    
+    let beam_commit = BeamCommit {
+        collection_hash: ticket.hash().to_string(),
+        sender: ticket.node_addr().node_id.to_string(),
+        sender_branch: thin.branch.clone(),
+        original_head: Some(thin.head.clone()),
+        created_at: metadata.created_at,
+    };
+    commit_received_tree(&beam_commit, &repo)?;
+    
+    let restored = restore_agent_sessions(&metadata, package_dir, &repo, false)?;
+    if let Some(convert_to) = convert_to {
+        convert_restored_session(&restored, convert_to, &repo)?;
+    }
+    
+    Ok(())
+}
+
+/// Commit the received tree on its `beam/<sender>/<branch>` tracking branch
+fn commit_received_tree(beam_commit: &BeamCommit, repo: &Path) -> Result<()> {
+    let commit = beam_commit.commit(repo)?;
+    println!("✓ Received tree committed as {} on {}", &commit[..12.min(commit.len())], beam_commit.branch_name());
+    println!("   Compare later changes with: git diff {}", beam_commit.branch_name());
+    
+    Ok(())
+}
+
+/// List auto-executing files in a received workspace and quarantine them unless trusted
+fn quarantine_auto_exec(target_dir: &Path, trust: bool) -> Result<()> {
+    let found = Quarantine::scan(target_dir)?;
+    if found.is_empty() {
+        return Ok(());
+    }
+    
+    println!();
+    println!("{} Found {} files that run commands automatically:", "⚠️".yellow(), found.len());
+    for file in &found {
+        println!("   {} ({})", file.path, file.kind.description());
+        for command in &file.commands {
+            println!("      $ {}", command);
+        }
+    }
+    
+    if trust {
+        println!("   Left active (--trust)");
+        return Ok(());
+    }
+    
+    Quarantine::apply(target_dir, &found)?;
+    println!("🔒 Quarantined them (hooks stripped, files renamed with {})", QUARANTINE_SUFFIX);
+    println!("   Review, then run: agentbeam trust {}", target_dir.display());
+    Ok(())
+}
+
+/// Report how a received workspace differs from what the sender signed
+fn verify_workspace(dir: &Path) -> Result<()> {
+    let Some(report) = manifest::verify_dir(dir)? else {
+        anyhow::bail!("No signed manifest in {}; was it received with AgentBeam?", dir.display());
+    };
+    
+    println!("{} Manifest signed by {}", "✓".green(), report.sender);
+    if !report.metadata_intact {
+        println!("{} .agentbeam-metadata.json was changed since the receive", "⚠️".yellow());
+    }
+    
+    let unchanged = report.with_state(FileState::Unchanged).count();
+    println!("   {} of {} files unchanged", unchanged, report.files.len());
+    
+    let sections = [
+        (FileState::Modified, "Modified locally since the receive"),
+        (FileState::Missing, "Missing"),
+        (FileState::Quarantined, "Quarantined on receive (original content intact)"),
+        (FileState::Consumed, "Unpacked into the git repository"),
+    ];
+    for (state, heading) in sections {
+        let entries: Vec<_> = report.with_state(state).collect();
+        if entries.is_empty() {
+            continue;
+        }
+        println!("{} ({}):", heading, entries.len());
+        for entry in entries {
+            println!("  - {}", entry.path);
+        }
+    }
+    
+    if report.is_intact() {
+        println!("{} Workspace matches the beam", "✓".green());
+    }
+    Ok(())
+}
+
+/// Print a delivery receipt and keep it as proof of delivery
+fn store_receipt(signed: &SignedReceipt) -> Result<()> {
+    let (receiver, receipt) = signed.verify()?;
+    println!("🧾 Delivery receipt signed by {}", receiver);
+    println!("   Collection: {}", receipt.collection);
+    println!("   Files:      {}", receipt.file_count);
+    if let Some(slug) = &receipt.target_slug {
+        println!("   Restored:   {}", slug);
+    }
+    println!("   Received:   {}", ticket::format_time(receipt.received_at));
+    
+    let path = signed.save(&receipt)?;
+    println!("   Saved to {}", path.display());
+    Ok(())
+}
+
+/// Append a record to the audit ledger; a ledger that cannot be written never fails the beam
+fn log_beam(record: &LedgerRecord, manifest: Option<&[u8]>) {
+    if let Err(e) = Ledger::open().and_then(|ledger| ledger.append(record, manifest)) {
+        println!("{} Could not record the beam in the history: {:#}", "⚠️".yellow(), e);
+    }
+}
+
+/// Log a receive, from the beam's metadata or, if it never arrived, the ticket preview
+fn record_received<T>(ticket: &BlobTicket, summary: Option<&TicketSummary>, dir: &Path, result: &Result<T>) {
+    let metadata = BeamMetadata::load(&dir.join(manifest::METADATA_PATH)).ok();
+    let (workspace_name, file_count, total_size) = match (&metadata, summary) {
+        (Some(metadata), _) => (metadata.workspace_name.clone(), metadata.file_count as u64, metadata.total_size),
+        (None, Some(summary)) => (summary.workspace_name.clone(), summary.file_count, summary.total_size),
+        (None, None) => ("unknown".to_string(), 0, 0),
+    };
+    
+    let record = LedgerRecord {
+        timestamp: ticket::unix_now(),
+        direction: Direction::Received,
+        collection: ticket.hash().to_string(),
+        workspace_name,
+        file_count,
+        total_size,
+        peer: Some(ticket.node_addr().node_id.to_string()),
+        peer_name: summary.and_then(|summary| summary.sender_name.clone()),
+        sessions: metadata
+            .iter()
+            .flat_map(|metadata| &metadata.agent_sessions)
+            .map(|session| format!("{} {}", session.agent, session.session_id))
+            .collect(),
+        redactions: Vec::new(),
+        secret_findings: Vec::new(),
+        outcome: if result.is_ok() { Outcome::Received } else { Outcome::Failed },
+        detail: result.as_ref().err().map(|e| format!("{:#}", e)),
+    };
+    log_beam(&record, ledger::read_manifest(dir).as_deref());
+}
+
+/// List ledger records matching `filter`, oldest first
+fn show_history(filter: &HistoryFilter, json: bool) -> Result<()> {
+    let ledger = Ledger::open()?;
+    let records: Vec<_> = ledger
+        .records()?
+        .into_iter()
+        .filter(|record| filter.matches(record))
+        .collect();
+    
+    if json {
+        for record in &records {
+            println!("{}", serde_json::to_string(record)?);
+        }
+        return Ok(());
+    }
+    
+    if records.is_empty() {
+        println!("No matching beams in {}", ledger.path().display());
+        return Ok(());
+    }
+    
+    for record in &records {
+        let arrow = match record.direction {
+            Direction::Sent => "⬆".blue(),
+            Direction::Received => "⬇".cyan(),
+        };
+        let outcome = match record.outcome {
+            Outcome::Delivered | Outcome::Received => record.outcome.to_string().green(),
+            Outcome::Expired | Outcome::Cancelled => record.outcome.to_string().yellow(),
+            Outcome::Failed => record.outcome.to_string().red(),
+        };
+        let peer = match (&record.peer_name, &record.peer) {
+            (Some(name), _) => name.clone(),
+            (None, Some(peer)) => peer.chars().take(16).collect(),
+            (None, None) => "-".to_string(),
+        };
+        println!("{} {}  {}  {}  {} files, {:.1}MB  {}  {}",
+            arrow,
+            ticket::format_time(record.timestamp),
+            record.collection.chars().take(12).collect::<String>(),
+            record.workspace_name,
+            record.file_count,
+            record.total_size as f64 / 1_000_000.0,
+            peer,
+            outcome);
+    }
+    Ok(())
+}
+
+/// Print every record of one beam and the manifest kept for it
+fn show_history_entry(prefix: &str) -> Result<()> {
+    let ledger = Ledger::open()?;
+    let records = ledger.find(prefix)?;
+    let Some(first) = records.first() else {
+        anyhow::bail!("No beam {} in {}", prefix, ledger.path().display());
+    };
+    if records.iter().any(|record| record.collection != first.collection) {
+        anyhow::bail!("{} matches more than one beam; give more of the hash", prefix);
+    }
+    
+    println!("📦 Beam {}", first.collection);
+    for record in &records {
+        println!();
+        println!("   {} {} ({})", ticket::format_time(record.timestamp), record.direction, record.outcome);
+        println!("   Workspace:  {}", record.workspace_name);
+        println!("   Size:       {:.1}MB in {} files", record.total_size as f64 / 1_000_000.0, record.file_count);
+        if let Some(peer) = &record.peer {
+            match &record.peer_name {
+                Some(name) => println!("   Peer:       {} ({})", name, peer),
+                None => println!("   Peer:       {}", peer),
+            }
+        }
+        for session in &record.sessions {
+            println!("   Session:    {}", session);
+        }
+        for redaction in &record.redactions {
+            println!("   Redacted:   {}", redaction);
+        }
+        for finding in &record.secret_findings {
+            println!("   Secret:     {}", finding);
+        }
+        if let Some(detail) = &record.detail {
+            println!("   Detail:     {}", detail);
+        }
+    }
+    
+    println!();
+    let Some(signed) = ledger.manifest(&first.collection)? else {
+        println!("No manifest was kept for this beam");
+        return Ok(());
+    };
+    print_manifest(&signed);
+    Ok(())
+}
+
+/// List the entries of a kept manifest, warning if its signature does not check out
+fn print_manifest(signed: &SignedManifest) {
+    let manifest = match signed.verify() {
+        Ok((sender, manifest)) => {
+            println!("{} Manifest signed by {}", "✓".green(), sender);
+            manifest
+        }
+        Err(e) => {
+            println!("{} Manifest does not verify: {:#}", "⚠️".yellow(), e);
+            match serde_json::from_str::<BeamManifest>(signed.manifest.get()) {
+                Ok(manifest) => manifest,
+                Err(_) => return,
+            }
+        }
+    };
+    
+    println!("   {} files, {:.1}MB, created {}",
+        manifest.entries.len(),
+        manifest.total_size as f64 / 1_000_000.0,
+        ticket::format_time(manifest.created_at));
+    for entry in &manifest.entries {
+        let role = format!("{:?}", entry.role).to_lowercase();
+        println!("  {:o}  {:>12}  {}  {:<9}  {}",
+            entry.mode,
+            entry.size,
+            &entry.hash[..12.min(entry.hash.len())],
+            role,
+            entry.path);
+    }
+}
+
+/// Decode a ticket and verify its signature without connecting to the sender
+fn inspect_ticket(ticket_str: &str) -> Result<()> {
+    let input: TicketInput = ticket_str.parse()?;
+    let blob = input.blob();
+    
+    let TicketInput::AgentBeam(ticket) = &input else {
+        println!("{} Plain blob ticket from an older AgentBeam, no preview available", "ℹ️".cyan());
+        println!("   Sender:     {}", blob.node_addr().node_id);
+        println!("   Collection: {}", blob.hash());
+        return Ok(());
+    };
+    
+    let summary = ticket.verify()?;
+    println!("{} Ticket signed by {}", "✓".green(), blob.node_addr().node_id);
+    print_ticket_summary(summary);
+    println!("   Collection: {}", blob.hash());
+    if summary.is_expired() {
+        println!("{} This ticket has expired", "⚠️".yellow());
+    }
+    Ok(())
+}
+
+/// Check a ticket before connecting, showing its preview, and return the blob ticket to fetch
+fn open_ticket(input: TicketInput) -> Result<(BlobTicket, Option<TicketSummary>)> {
+    let summary = input.check()?.cloned();
+    if let Some(summary) = &summary {
+        println!("📦 Incoming beam");
+        print_ticket_summary(summary);
+        println!();
+    }
+    Ok((input.blob().clone(), summary))
+}
+
+fn print_ticket_summary(summary: &TicketSummary) {
+    println!("   Workspace:  {}", summary.workspace_name);
+    if let Some(name) = &summary.sender_name {
+        println!("   From:       {}", name);
+    }
+    println!("   Size:       {:.1}MB in {} files", summary.total_size as f64 / 1_000_000.0, summary.file_count);
+    if !summary.agents.is_empty() {
+        println!("   Sessions:   {}", summary.agents.join(", "));
+    }
+    println!("   Created:    {}", ticket::format_time(summary.created_at));
+    match summary.expires_at {
+        Some(expires_at) => println!("   Expires:    {}", ticket::format_time(expires_at)),
+        None => println!("   Expires:    never"),
+    }
+}
+
+/// Put back what `receive` quarantined
+fn trust_workspace(dir: &Path) -> Result<()> {
+    let outcome = Quarantine::trust(dir)?;
+    if outcome.restored.is_empty() && outcome.kept.is_empty() {
+        println!("Nothing is quarantined in {}", dir.display());
+        return Ok(());
+    }
+    
+    for file in &outcome.restored {
+        println!("{} Restored {} ({})", "✓".green(), file.path, file.kind.description());
+    }
+    for file in &outcome.kept {
+        println!("{} {} changed since it was received; merge {} by hand",
+            "⚠️".yellow(), file.path, file.quarantined_path());
+    }
+    Ok(())
+}
+
+/// Initialize an empty repository for the received tree on the sender's branch
+fn init_received_repo(target_dir: &Path, branch: &str) -> Result<()> {
+    println!("Initializing git repository...");
+    GitRepo::init(target_dir, branch)
+        .with_context(|| format!("Failed to initialize git repository in {}", target_dir.display()))?;
+    println!("✓ Git initialized on branch: {}", branch);
+    Ok(())
+}
+
+/// Summarize worktree, submodule and LFS state at the consent prompt
+fn print_repo_layout(layout: &RepoLayout) {
+    if layout.worktree {
+        println!("🌳 Workspace is a linked git worktree; the receiver gets a standalone repository");
+    }
+    if !layout.submodules.is_empty() {
+        println!("📦 {} submodules, restored at their pinned commits on receive:", layout.submodules.len());
+        for submodule in &layout.submodules {
+            println!("   - {} @ {} ({})", submodule.path, &submodule.sha[..12.min(submodule.sha.len())],
+                submodule.url.as_deref().unwrap_or("no URL"));
+        }
+    }
+    if !layout.lfs_files.is_empty() {
+        let pointers = layout.lfs_pointers_only();
+        println!("📦 {} Git LFS files ({} checked out as pointers only)", layout.lfs_files.len(), pointers);
+        if pointers > 0 {
+            println!("   Use --include-lfs to ship their local objects");
+        }
+    }
+}
+
+/// Restore submodules and shipped LFS objects into a received repository
+fn restore_repo_layout(layout: &RepoLayout, repo: &Path) -> Result<()> {
+    if !layout.submodules.is_empty() {
+        let failed = git_layout::restore_submodules(repo, &layout.submodules)?;
+        println!("✓ Restored {} of {} submodules", layout.submodules.len() - failed.len(), layout.submodules.len());
+        for path in &failed {
+            println!("   ⚠️  {} kept as plain files; its pinned commit could not be fetched", path);
+        }
+    }
+    
+    if !layout.lfs_files.is_empty() {
+        let installed = git_layout::restore_lfs_objects(repo, repo, &layout.lfs_files)?;
+        let pointers = layout.lfs_pointers_only();
+        println!("📦 {} Git LFS files: {} materialized by the sender, {} objects shipped",
+            layout.lfs_files.len(), layout.lfs_files.len() - pointers, installed);
+        if pointers > installed {
+            println!("   Run `git lfs pull` to fetch the remaining content");
+        }
+    }
+    
+    Ok(())
+}
+
+/// List the credentials found in `files` and apply the configured policy
+///
+/// Returns the findings that were let through or excluded, for the ledger.
+fn apply_secret_policy(
+    collector: &FileCollector,
+    files: &mut Vec<(String, PathBuf)>,
+    policy: SecretPolicy,
+) -> Result<Vec<String>> {
+    let findings = collector.scan_for_secrets(files)?;
+    if findings.is_empty() {
+        return Ok(Vec::new());
+    }
+    
+    println!("{} Possible secrets found in {} locations:", "⚠️".yellow(), findings.len());
+    for finding in &findings {
+        println!("  - {}:{} ({})", finding.relative_path, finding.line, finding.rule);
+    }
+    println!();
+    
+    match policy {
+        SecretPolicy::Block => {
+            anyhow::bail!(
+                "Refusing to beam workspace with possible secrets\n\
+                 Remove them, add the files to .beamignore, or use --secret-policy warn|exclude"
+            );
+        }
+        SecretPolicy::Warn => {
+            println!("{} Continuing with these files included", "⚠️".yellow());
+        }
+        SecretPolicy::Exclude => {
+            let excluded = collector.exclude_findings(files, &findings)?;
+            println!("✓ Excluded {} files and added them to .beamignore", excluded.len());
+        }
+    }
+    println!();
+    
+    Ok(findings
+        .iter()
+        .map(|finding| format!("{}:{} ({})", finding.relative_path, finding.line, finding.rule))
+        .collect())
+}
+
+/// List what committed history carries beyond the workspace
+///
+/// With `--yes` this refuses, since nobody reviews the list, unless the secret policy is `warn`.
+fn report_history_audit(audit: &HistoryAudit, policy: SecretPolicy, skip_confirm: bool) -> Result<()> {
+    if audit.is_empty() {
+        return Ok(());
+    }
+    
+    if !audit.withheld.is_empty() {
+        println!("{} Left out or sanitized in the workspace, but committed in the history that will be shared:", "⚠️".yellow());
+        for path in &audit.withheld {
+            println!("  - {}", path);
+        }
+        println!();
+    }
+    
+    if !audit.findings.is_empty() {
+        println!("{} Possible secrets in committed history ({} locations):", "⚠️".yellow(), audit.findings.len());
+        for finding in &audit.findings {
+            println!("  - {}:{} ({})", finding.relative_path, finding.line, finding.rule);
+        }
+        println!();
+        if policy != SecretPolicy::Warn {
+            anyhow::bail!(
+                "Refusing to beam git history with possible secrets\n\
+                 Rewrite the history, use --session-only, or use --secret-policy warn"
+            );
+        }
+    }
+    
+    if skip_confirm {
+        if policy == SecretPolicy::Warn {
+            println!("{} Continuing with this history included", "⚠️".yellow());
+            println!();
+        } else {
+            anyhow::bail!(
+                "Refusing to share this history without confirmation\n\
+                 Run without --yes to review it, or use --secret-policy warn"
+            );
+        }
+    }
+    Ok(())
+}
+
+/// List every change made to Claude config before it is shared
+fn print_config_changes(changes: &[ConfigChange]) {
+    if changes.is_empty() {
+        return;
+    }
+    
+    println!("🔏 Claude config changed before sharing (--claude-config keep to share as is):");
+    for change in changes {
+        println!("  - {}: {}", change.path, change.description);
+    }
+    println!();
+}
+
+/// One line per config change, as kept in the ledger
+fn describe_config_changes(changes: &[ConfigChange]) -> Vec<String> {
+    changes
+        .iter()
+        .map(|change| format!("{}: {}", change.path, change.description))
+        .collect()
+}
+
+/// Print agent-modified files, marking those no longer present in `workspace`
+fn print_touched_files(touched_files: &[TouchedFile], workspace: &Path) {
+    const MAX_LISTED: usize = 20;
+    
+    if touched_files.is_empty() {
+        return;
+    }
+    println!("   Files modified by the agent: {}", touched_files.len());
+    for touched in touched_files.iter().take(MAX_LISTED) {
+        let deleted = if workspace.join(&touched.path).exists() { "" } else { " (deleted)" };
+        println!("     - {}{}", touched.path, deleted);
+    }
+    if touched_files.len() > MAX_LISTED {
+        println!("     … and {} more", touched_files.len() - MAX_LISTED);
+    }
+}
+
+/// Latest sessions of every non-Claude agent with state for the workspace
+fn detect_other_agent_sessions(
+    workspace: &Path,
+    config: &BeamConfig,
+) -> Result<Vec<(Box<dyn AgentAdapter>, AgentSession)>> {
+    let mut sessions = Vec::new();
+    for adapter in agents::adapters() {
+        if adapter.kind() == AgentKind::ClaudeCode || !config.includes_agent(adapter.kind()) {
+            continue;
+        }
+        if let Some(session) = adapter.latest_session(workspace)? {
+            sessions.push((adapter, session));
+        }
+    }
+    Ok(sessions)
+}
+
+/// Tell the receiver about metadata from a newer minor schema version
+fn print_schema_notes(metadata: &BeamMetadata) {
+    if metadata.schema_version <= CURRENT_SCHEMA {
+        return;
+    }
+    
+    println!("{} Beam metadata uses schema {} (this AgentBeam writes {})",
+        "ℹ️".cyan(), metadata.schema_version, CURRENT_SCHEMA);
+    let unknown = metadata.unknown_fields();
+    if !unknown.is_empty() {
+        println!("   Ignoring fields added since: {}", unknown.join(", "));
+    }
+}
+
+/// Dispatch each beamed session to its agent's adapter
+fn restore_agent_sessions(
+    metadata: &BeamMetadata,
+    package_dir: &Path,
+    target_dir: &Path,
+    write_handoff: bool,
+) -> Result<Vec<RestoredSession>> {
+    let mut restored_sessions = Vec::new();
+    
+    for info in &metadata.agent_sessions {
+        println!();
+        println!("📎 Restoring {} session into {}...", info.agent, target_dir.display());
+        
+        let Some(adapter) = agents::adapter_for(info.agent) else {
+            println!("⚠️  This version of AgentBeam cannot restore {} sessions", info.agent);
+            continue;
+        };
+        
+        let restored = adapter.restore(info, package_dir, target_dir)?;
+        println!("✓ {} session restored ({} entries)", info.agent, info.entry_count);
+        println!("   Resume with: {}", restored.resume_hint);
+        
+        if info.agent == AgentKind::ClaudeCode {
+            if let Some(stats) = &metadata.session_stats {
+                stats.print_summary();
+            }
+            if let Some(claude_info) = &metadata.claude_session && claude_info.trimmed_entry_count > 0 {
+                println!("   Sender trimmed {} earlier entries", claude_info.trimmed_entry_count);
+            }
+            
+            if write_handoff {
+                let handoff_path = handoff::write_received_handoff(
+                    target_dir,
+                    &package_dir.join(CLAUDE_SESSION_PATH),
+                    &info.session_id,
+                    &metadata.workspace_name,
+                )?;
+                println!("📝 Handoff summary written to {}", handoff_path.display());
+            }
+        }
+        
+        restored_sessions.push(restored);
+    }
+    
+    Ok(restored_sessions)
+}
+
+/// Convert the restored Claude session, whose paths already point at `workspace`
+fn convert_restored_session(
+    restored: &[RestoredSession],
+    target: ConvertTarget,
+    workspace: &Path,
+) -> Result<()> {
+    let Some(claude) = restored.iter().find(|r| r.agent == AgentKind::ClaudeCode) else {
+        println!("{} No Claude Code session to convert for {}", "⚠️".yellow(), target.agent());
+        return Ok(());
+    };
+    
+    let transcript = SessionTranscript::load(&claude.location)?;
+    let workspace = workspace.canonicalize()?;
+    report_conversion(&transcript, target, &workspace)
+}
+
+fn report_conversion(transcript: &SessionTranscript, target: ConvertTarget, workspace: &Path) -> Result<()> {
+    println!();
+    println!("🔁 Converting Claude Code session for {}...", target.agent());
+    
+    let converted = convert_session(transcript, target, workspace)?;
+    println!("✓ {} session written to {}", converted.restored.agent, converted.restored.location.display());
+    if converted.notes > 0 {
+        println!("   {} tool calls or entries without an equivalent were kept as text notes", converted.notes);
+    }
+    println!("   Resume with: {}", converted.restored.resume_hint);
+    
+    Ok(())
+}
+
+/// A session transcript located from a file, a received workspace or a ticket
+struct SessionSource {
+    session_file: PathBuf,
+    title: String,
+    _staging_guard: Option<TempDirGuard>,
+}
+
+async fn resolve_session_source(source: &str, config: BeamConfig) -> Result<SessionSource> {
+    let path = PathBuf::from(source);
+    
+    if path.is_file() {
+        let title = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("session")
+            .to_string();
+        return Ok(SessionSource {
+            session_file: path,
+            title,
+            _staging_guard: None,
+        });
+    }
+    
+    let (workspace_dir, staging_guard) = if path.is_dir() {
+        (path, None)
+    } else {
+        let (ticket, summary) = open_ticket(
+            TicketInput::from_str(source)
+                .context("Source is neither a session file, a received workspace, nor a valid ticket")?,
+        )?;
+        
+        let staging_dir = std::env::temp_dir().join(format!(
+            "{}render-{}",
+            TEMP_DIR_PREFIX,
+            hex::encode(rand::random::<[u8; 8]>())
+        ));
+        let guard = TempDirGuard::new(staging_dir.clone());
+        
+        let agent_beam = AgentBeam::new(config).await?;
+        let mp = MultiProgress::new();
+        let receiver = Receiver::new(&agent_beam.endpoint, &agent_beam.blobs, Some(&mp));
+        let result = receiver.receive_from_ticket(&ticket, &staging_dir).await;
+        record_received(&ticket, summary.as_ref(), &staging_dir, &result);
+        // Rendering leaves nothing to restore, so the beam is delivered once it verifies
+        receiver.finish(&ticket, result?, Ok(())).await?;
+        agent_beam.shutdown().await?;
+        
+        (staging_dir, Some(guard))
+    };
+    
+    let session_file = workspace_dir.join(CLAUDE_SESSION_PATH);
+    if !session_file.exists() {
+        anyhow::bail!("No Claude session found in {}", workspace_dir.display());
+    }
+    
+    let metadata_path = workspace_dir.join(".agentbeam-metadata.json");
+    let title = std::fs::read_to_string(&metadata_path)
+        .ok()
+        .and_then(|content| BeamMetadata::from_json(&content).ok())
+        .map(|metadata| metadata.workspace_name)
+        .unwrap_or_else(|| "session".to_string());
+    
+    Ok(SessionSource {
+        session_file,
+        title,
+        _staging_guard: staging_guard,
+    })
+}
+
+async fn render_session(
+    source: String,
+    format: RenderFormat,
+    output: Option<PathBuf>,
+    options: RenderOptions,
+    config: BeamConfig,
+) -> Result<()> {
+    let source = resolve_session_source(&source, config).await?;
+    let transcript = SessionTranscript::load(&source.session_file)?;
+    if !transcript.malformed.is_empty() {
+        eprintln!("{} Skipping {} malformed session lines", "⚠️".yellow(), transcript.malformed.len());
+    }
+    
+    let rendered = SessionRenderer::new(options).render(&transcript, &source.title, format);
+    
+    let output = output
+        .unwrap_or_else(|| PathBuf::from(format!("{}.{}", source.title, format.extension())));
+    if output.as_os_str() == "-" {
+        print!("{}", rendered);
+    } else {
+        std::fs::write(&output, rendered)?;
+        println!("{} Rendered {} entries to {}", "✓".green(), transcript.len(), output.display());
+    }
+    
+    Ok(())
+}
+
+async fn convert_session_command(
+    source: String,
+    target: ConvertTarget,
+    workspace: Option<PathBuf>,
+    config: BeamConfig,
+) -> Result<()> {
+    let source = resolve_session_source(&source, config).await?;
+    let transcript = SessionTranscript::load(&source.session_file)?;
+    if !transcript.malformed.is_empty() {
+        eprintln!("{} Skipping {} malformed session lines", "⚠️".yellow(), transcript.malformed.len());
+    }
+    
+    let workspace = match workspace {
+        Some(path) => path,
+        None => std::env::current_dir()?,
+    };
+    let workspace = workspace
+        .canonicalize()
+        .with_context(|| format!("Workspace {} does not exist", workspace.display()))?;
+    
+    report_conversion(&transcript, target, &workspace)
+}
+
+fn ensure_gitignore_has_agentbeam_pattern(workspace_dir: &Path) -> Result<()> {
+    let gitignore_path = workspace_dir.join(".gitignore");
+    let pattern = ".agentbeam-*";
+
+    // Read existing gitignore or create empty content
+    let mut content = if gitignore_path.exists() {
+        std::fs::read_to_string(&gitignore_path)?
+    } else {
+        String::new()
+    };
+
+    // Check if pattern already exists
+    let has_pattern = content.lines().any(|line| {
+        let trimmed = line.trim();
+        trimmed == pattern || trimmed == ".agentbeam-*/"
+    });
+
+    if !has_pattern {
+        // Add the pattern
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+
+        // Add a comment if this is the first entry or after existing content
+        if content.is_empty() {
+            content.push_str("# AgentBeam temporary directories\n");
+        } else {
+            content.push_str("\n# AgentBeam temporary directories\n");
+        }
+        content.push_str(pattern);
+        content.push('\n');
+
+        // Write back to file
+        std::fs::write(&gitignore_path, content)?;
+        println!("✓ Added {} to .gitignore", pattern);
     }
 
     Ok(())
@@ -442,4 +2052,165 @@ async fn cleanup_test_data() -> Result<()> {
         println!("No test directory found");
     }
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::git_transfer::git;
+    use std::fs;
+    use std::sync::OnceLock;
+    use tempfile::TempDir;
+
+    const SENDER_WORKSPACE: &str = "/home/charlie/src/webapp";
+
+    /// Point Claude at a scratch config directory shared by every test in this process
+    fn claude_dir() -> &'static Path {
+        static DIR: OnceLock<TempDir> = OnceLock::new();
+        DIR.get_or_init(|| {
+            let dir = TempDir::new().unwrap();
+            // SAFETY: set once, before any test reads it, and never changed afterwards
+            unsafe { std::env::set_var("CLAUDE_CONFIG_DIR", dir.path()) };
+            dir
+        })
+        .path()
+    }
+
+    fn checkout(path: &Path, origin: Option<&str>, source: &str) -> String {
+        fs::create_dir_all(path).unwrap();
+        git(path, &["init", "--quiet", "--initial-branch", "main"]).unwrap();
+        git(path, &["config", "user.email", "test@example.com"]).unwrap();
+        git(path, &["config", "user.name", "Test"]).unwrap();
+        fs::write(path.join("lib.rs"), source).unwrap();
+        git(path, &["add", "."]).unwrap();
+        git(path, &["commit", "--quiet", "-m", "initial"]).unwrap();
+        if let Some(origin) = origin {
+            git(path, &["remote", "add", "origin", origin]).unwrap();
+        }
+        git(path, &["rev-parse", "HEAD"]).unwrap().trim().to_string()
+    }
+
+    /// A received session-only beam from `SENDER_WORKSPACE` at `head`
+    fn staging(dir: &Path, remote_url: Option<&str>, head: &str) -> PathBuf {
+        let staging_dir = dir.join("staging");
+        fs::create_dir_all(staging_dir.join(".agentbeam")).unwrap();
+        let lines = [
+            serde_json::json!({
+                "type": "user", "uuid": "a", "parentUuid": null, "sessionId": "original",
+                "cwd": SENDER_WORKSPACE, "timestamp": "2025-01-01T10:00:00.000Z",
+                "message": {"role": "user", "content": "fix the build"},
+            }),
+            serde_json::json!({
+                "type": "assistant", "uuid": "b", "parentUuid": "a", "sessionId": "original",
+                "cwd": SENDER_WORKSPACE, "timestamp": "2025-01-01T10:00:01.000Z",
+                "message": {"role": "assistant", "content": [{
+                    "type": "tool_use", "id": "t", "name": "Edit",
+                    "input": {"file_path": format!("{}/lib.rs", SENDER_WORKSPACE)},
+                }]},
+            }),
+        ];
+        let session: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+        fs::write(staging_dir.join(CLAUDE_SESSION_PATH), session).unwrap();
+
+        let metadata = BeamMetadata {
+            schema_version: CURRENT_SCHEMA,
+            session_id: "session-0000000000000000".to_string(),
+            workspace_name: "webapp".to_string(),
+            created_at: 0,
+            beam_version: "0.1.0".to_string(),
+            total_size: 0,
+            file_count: 0,
+            claude_session: None,
+            git_context: Some(GitContext {
+                branch: "main".to_string(),
+                has_uncommitted_changes: false,
+                remote_url: remote_url.map(str::to_string),
+                head_commit: Some(head.to_string()),
+                stash_count: 0,
+                layout: RepoLayout::default(),
+            }),
+            session_stats: None,
+            session_only: true,
+            agent_sessions: vec![agents::AgentSessionInfo {
+                agent: AgentKind::ClaudeCode,
+                session_id: "original".to_string(),
+                entry_count: 2,
+                files: vec![CLAUDE_SESSION_PATH.to_string()],
+                source_workspace: Some(SENDER_WORKSPACE.to_string()),
+            }],
+            agent_touched_files: Vec::new(),
+            agent_touched_only: false,
+            git_bundle: None,
+            thin_beam: None,
+            extra: Default::default(),
+        };
+        fs::write(
+            staging_dir.join(".agentbeam-metadata.json"),
+            serde_json::to_string(&metadata).unwrap(),
+        )
+        .unwrap();
+        staging_dir
+    }
+
+    #[test]
+    fn test_attach_restores_session_under_the_checkout() {
+        let claude_dir = claude_dir();
+        let temp_dir = TempDir::new().unwrap();
+        let checkout_dir = temp_dir.path().join("webapp");
+        let head = checkout(&checkout_dir, Some("https://github.com/charlie/webapp"), "fn main() {}\n");
+        let staging_dir = staging(temp_dir.path(), Some("git@github.com:charlie/webapp.git"), &head);
+
+        attach_received_session(&staging_dir, &checkout_dir, None).unwrap();
+
+        let checkout_dir = checkout_dir.canonicalize().unwrap();
+        let project_dir = claude_dir.join("projects").join(ClaudeContext::path_to_slug(&checkout_dir));
+        let sessions: Vec<PathBuf> = fs::read_dir(&project_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+            .collect();
+        assert_eq!(sessions.len(), 1);
+
+        let new_id = sessions[0].file_stem().unwrap().to_str().unwrap();
+        let content = fs::read_to_string(&sessions[0]).unwrap();
+        assert!(!content.contains(SENDER_WORKSPACE));
+        assert_eq!(content.lines().count(), 2);
+        for line in content.lines() {
+            let entry: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(entry["cwd"], checkout_dir.to_str().unwrap());
+            assert_eq!(entry["sessionId"], new_id);
+        }
+        assert!(content.contains(checkout_dir.join("lib.rs").to_str().unwrap()));
+    }
+
+    #[test]
+    fn test_attach_refuses_another_repository() {
+        claude_dir();
+        let temp_dir = TempDir::new().unwrap();
+
+        let other = temp_dir.path().join("other");
+        let head = checkout(&other, Some("git@github.com:someone/other.git"), "fn main() {}\n");
+        let staging_dir = staging(temp_dir.path(), Some("git@github.com:charlie/webapp.git"), &head);
+        let err = attach_received_session(&staging_dir, &other, None).unwrap_err();
+        assert!(err.to_string().contains("charlie/webapp"), "{}", err);
+
+        // Without remotes to compare, the checkout must already have the sender's HEAD
+        let unrelated = temp_dir.path().join("unrelated");
+        checkout(&unrelated, None, "fn other() {}\n");
+        let staging_dir = staging(temp_dir.path(), None, &head);
+        assert!(attach_received_session(&staging_dir, &unrelated, None).is_err());
+
+        let plain = temp_dir.path().join("plain");
+        fs::create_dir_all(&plain).unwrap();
+        assert!(attach_received_session(&staging_dir, &plain, None).is_err());
+
+        assert!(!claude_dir().join("projects").join(ClaudeContext::path_to_slug(&other)).exists());
+    }
+
+    #[test]
+    fn test_repository_key_ignores_url_form() {
+        assert_eq!(repository_key("git@github.com:charlie/webapp.git"), "github.com/charlie/webapp");
+        assert_eq!(repository_key("https://GitHub.com/charlie/webapp/"), "github.com/charlie/webapp");
+        assert_eq!(repository_key("ssh://git@github.com/charlie/webapp.git"), "github.com/charlie/webapp");
+        assert_ne!(repository_key("https://github.com/charlie/webapp"), repository_key("https://github.com/charlie/api"));
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+use super::{AgentAdapter, AgentKind, AgentSession, AgentSessionInfo, RestoredSession};
+use crate::core::secret_scanner::SecretScanner;
+
+pub const AIDER_CHAT_HISTORY: &str = ".aider.chat.history.md";
+pub const AIDER_INPUT_HISTORY: &str = ".aider.input.history";
+pub const AIDER_CONFIG: &str = ".aider.conf.yml";
+
+/// Aider keeps its chat history and configuration inside the repository
+///
+/// Aider's default `.gitignore` entry excludes these files, so they are
+/// force-included here regardless of ignore rules.
+pub struct AiderAdapter;
+
+impl AiderAdapter {
+    /// User messages in the chat history are `#### ` headings
+    fn count_turns(history: &Path) -> Result<usize> {
+        Ok(fs::read_to_string(history)?
+            .lines()
+            .filter(|line| line.starts_with("#### "))
+            .count())
+    }
+}
+
+impl AgentAdapter for AiderAdapter {
+    fn kind(&self) -> AgentKind {
+        AgentKind::Aider
+    }
+
+    fn entry_unit(&self) -> &'static str {
+        "chat turns"
+    }
+
+    fn detect(&self, workspace: &Path) -> Result<bool> {
+        Ok(workspace.join(AIDER_CHAT_HISTORY).is_file())
+    }
+
+    fn list_sessions(&self, workspace: &Path) -> Result<Vec<AgentSession>> {
+        let history = workspace.join(AIDER_CHAT_HISTORY);
+        if !history.is_file() {
+            return Ok(Vec::new());
+        }
+
+        let aux_files = [AIDER_INPUT_HISTORY, AIDER_CONFIG]
+            .iter()
+            .map(|name| workspace.join(name))
+            .filter(|path| path.is_file())
+            .collect();
+
+        Ok(vec![AgentSession {
+            agent: AgentKind::Aider,
+            session_id: "aider".to_string(),
+            entry_count: Self::count_turns(&history)?,
+            session_file: history,
+            aux_files,
+        }])
+    }
+
+    fn collect_files(&self, session: &AgentSession) -> Result<Vec<(String, PathBuf)>> {
+        let mut files = Vec::new();
+
+        for path in std::iter::once(&session.session_file).chain(&session.aux_files) {
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+
+            // The config often carries API keys; never force it past the secret scanner
+            if name == AIDER_CONFIG && !SecretScanner::scan_file(name, path)?.is_empty() {
+                warn!("Leaving out {} because it appears to contain secrets", name);
+                println!("⚠️  Leaving out {} because it appears to contain secrets", name);
+                continue;
+            }
+
+            files.push((name.to_string(), path.clone()));
+        }
+
+        Ok(files)
+    }
+
+    fn restore(
+        &self,
+        info: &AgentSessionInfo,
+        package_dir: &Path,
+        target_dir: &Path,
+    ) -> Result<RestoredSession> {
+        // A full beam already exported these files into the workspace
+        if package_dir != target_dir {
+            for name in &info.files {
+                let source = package_dir.join(name);
+                let dest = target_dir.join(name);
+                if !source.exists() {
+                    continue;
+                }
+
+                if dest.exists() && fs::read(&dest)? != fs::read(&source)? {
+                    let backup = target_dir.join(format!("{}.bak", name));
+                    println!("   Keeping existing {} as {}", name, backup.display());
+                    fs::rename(&dest, &backup)?;
+                }
+                fs::copy(&source, &dest)?;
+            }
+        }
+
+        let history = target_dir.join(AIDER_CHAT_HISTORY);
+        if let Some(source_workspace) = &info.source_workspace {
+            let abs_target = target_dir.canonicalize()?;
+            for name in &info.files {
+                let rewritten =
+                    self.relocate_paths(&target_dir.join(name), Path::new(source_workspace), &abs_target)?;
+                debug!("Relocated {} paths in {}", rewritten, name);
+            }
+        }
+
+        Ok(RestoredSession {
+            agent: AgentKind::Aider,
+            session_id: info.session_id.clone(),
+            location: history,
+            resume_hint: "aider --restore-chat-history".to_string(),
+        })
+    }
+
+    fn relocate_paths(&self, session_file: &Path, from: &Path, to: &Path) -> Result<usize> {
+        let from = from.to_string_lossy();
+        let to = to.to_string_lossy();
+        if from == to || !session_file.is_file() {
+            return Ok(0);
+        }
+
+        let content = fs::read_to_string(session_file)?;
+        let count = content.matches(from.as_ref()).count();
+        if count > 0 {
+            fs::write(session_file, content.replace(from.as_ref(), &to))?;
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detects_history_and_counts_turns() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path();
+        fs::write(
+            workspace.join(AIDER_CHAT_HISTORY),
+            "# aider chat started at 2025-01-01 10:00:00\n\n#### add a test\n\nOk.\n\n#### run it\n\nDone.\n",
+        )
+        .unwrap();
+        fs::write(workspace.join(AIDER_INPUT_HISTORY), "+add a test\n").unwrap();
+        fs::write(workspace.join(AIDER_CONFIG), "openai-api-key: sk-proj-abcdefghijklmnopqrstuvwxyz0123456789\n").unwrap();
+
+        let session = AiderAdapter.latest_session(workspace).unwrap().unwrap();
+        assert_eq!(session.entry_count, 2);
+
+        let files = AiderAdapter.collect_files(&session).unwrap();
+        let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec![AIDER_CHAT_HISTORY, AIDER_INPUT_HISTORY]);
+    }
+}
@@ -0,0 +1,102 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use super::{
+    relocate_json_paths, AgentAdapter, AgentKind, AgentSession, AgentSessionInfo, RestoredSession,
+};
+use crate::core::claude_session::{ClaudeContext, ClaudeSessionInfo, SessionTranscript};
+
+/// Collection path of the beamed Claude Code transcript
+pub const CLAUDE_SESSION_PATH: &str = ".agentbeam/claude-session.jsonl";
+
+/// Sessions stored by Claude Code under `~/.claude/projects/<slug>/*.jsonl`
+pub struct ClaudeAdapter;
+
+impl AgentAdapter for ClaudeAdapter {
+    fn kind(&self) -> AgentKind {
+        AgentKind::ClaudeCode
+    }
+
+    fn detect(&self, workspace: &Path) -> Result<bool> {
+        Ok(ClaudeContext::project_dir(workspace)?.is_dir())
+    }
+
+    fn list_sessions(&self, workspace: &Path) -> Result<Vec<AgentSession>> {
+        let project_dir = ClaudeContext::project_dir(workspace)?;
+        if !project_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        ClaudeContext::list_session_files(&project_dir)?
+            .into_iter()
+            .map(|path| Ok(ClaudeContext::load_session(workspace, path)?.agent_session()))
+            .collect()
+    }
+
+    fn collect_files(&self, session: &AgentSession) -> Result<Vec<(String, PathBuf)>> {
+        let mut files = vec![(CLAUDE_SESSION_PATH.to_string(), session.session_file.clone())];
+
+        for todo_file in &session.aux_files {
+            if let Some(name) = todo_file.file_name().and_then(|n| n.to_str()) {
+                files.push((format!(".agentbeam/todos/{}", name), todo_file.clone()));
+            }
+        }
+
+        Ok(files)
+    }
+
+    fn restore(
+        &self,
+        info: &AgentSessionInfo,
+        package_dir: &Path,
+        target_dir: &Path,
+    ) -> Result<RestoredSession> {
+        let session_source = package_dir.join(CLAUDE_SESSION_PATH);
+        if !session_source.exists() {
+            anyhow::bail!("Claude session file not found in package");
+        }
+
+        let claude_info = ClaudeSessionInfo {
+            original_session_id: info.session_id.clone(),
+            project_slug: info
+                .source_workspace
+                .as_deref()
+                .map(|w| ClaudeContext::path_to_slug(Path::new(w)))
+                .unwrap_or_default(),
+            entry_count: info.entry_count,
+            trimmed_entry_count: 0,
+        };
+
+        let restored = ClaudeContext::restore(target_dir, &claude_info, &session_source)?;
+
+        if let Some(source_workspace) = &info.source_workspace {
+            let abs_target = target_dir
+                .canonicalize()
+                .context("Failed to resolve target directory")?;
+            let rewritten =
+                self.relocate_paths(&restored.location, Path::new(source_workspace), &abs_target)?;
+            debug!("Relocated {} paths in restored Claude session", rewritten);
+        }
+
+        Ok(restored)
+    }
+
+    fn relocate_paths(&self, session_file: &Path, from: &Path, to: &Path) -> Result<usize> {
+        let from = from.to_string_lossy();
+        let to = to.to_string_lossy();
+        if from == to {
+            return Ok(0);
+        }
+
+        let mut transcript = SessionTranscript::load(session_file)?;
+        let mut count = 0;
+        for line in &mut transcript.lines {
+            count += relocate_json_paths(&mut line.raw, &from, &to);
+        }
+
+        fs::write(session_file, transcript.to_jsonl()?)?;
+        Ok(count)
+    }
+}
@@ -0,0 +1,353 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use tracing::{debug, trace};
+use walkdir::WalkDir;
+
+use super::{
+    is_under, relocate_json_paths, AgentAdapter, AgentKind, AgentSession, AgentSessionInfo,
+    RestoredSession,
+};
+
+/// Collection directory for beamed Codex rollouts, mirroring `sessions/YYYY/MM/DD`
+pub const CODEX_PACKAGE_DIR: &str = ".agentbeam/codex";
+
+/// Rollouts stored by Codex CLI under `$CODEX_HOME/sessions/YYYY/MM/DD/rollout-*.jsonl`
+#[derive(Debug, Clone, Default)]
+pub struct CodexAdapter {
+    /// Overrides `$CODEX_HOME` / `~/.codex`
+    pub home: Option<PathBuf>,
+}
+
+/// Header of a rollout file
+#[derive(Debug, Clone)]
+struct RolloutMeta {
+    session_id: String,
+    cwd: String,
+}
+
+impl CodexAdapter {
+    pub fn with_home(home: PathBuf) -> Self {
+        Self { home: Some(home) }
+    }
+
+    fn codex_home(&self) -> Result<PathBuf> {
+        if let Some(home) = &self.home {
+            return Ok(home.clone());
+        }
+        if let Ok(home) = std::env::var("CODEX_HOME") {
+            return Ok(PathBuf::from(home));
+        }
+        let home = dirs::home_dir().context("Failed to get home directory")?;
+        Ok(home.join(".codex"))
+    }
+
+    fn sessions_dir(&self) -> Result<PathBuf> {
+        Ok(self.codex_home()?.join("sessions"))
+    }
+
+    /// Read the session id and cwd from the rollout's `session_meta` line
+    fn read_meta(path: &Path) -> Result<Option<RolloutMeta>> {
+        let reader = BufReader::new(fs::File::open(path)?);
+        let Some(first) = reader.lines().next().transpose()? else {
+            return Ok(None);
+        };
+
+        let value: Value = match serde_json::from_str(&first) {
+            Ok(value) => value,
+            Err(e) => {
+                trace!("Skipping unparsable rollout {}: {}", path.display(), e);
+                return Ok(None);
+            }
+        };
+
+        // Current rollouts wrap the header in a `session_meta` item; older ones store it bare
+        let meta = match value.get("type").and_then(Value::as_str) {
+            Some("session_meta") => value.get("payload").cloned().unwrap_or(Value::Null),
+            _ => value,
+        };
+
+        let session_id = meta.get("id").and_then(Value::as_str);
+        let cwd = meta.get("cwd").and_then(Value::as_str);
+        Ok(session_id.zip(cwd).map(|(session_id, cwd)| RolloutMeta {
+            session_id: session_id.to_string(),
+            cwd: cwd.to_string(),
+        }))
+    }
+
+    fn rollouts(&self) -> Result<Vec<PathBuf>> {
+        let sessions_dir = self.sessions_dir()?;
+        if !sessions_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut rollouts: Vec<PathBuf> = WalkDir::new(&sessions_dir)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.into_path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with("rollout-") && n.ends_with(".jsonl"))
+            })
+            .collect();
+
+        // Paths embed the date and a timestamped file name, so they sort chronologically
+        rollouts.sort();
+        Ok(rollouts)
+    }
+
+    /// Write a new rollout where Codex looks for sessions started at `started`
+    pub fn write_rollout(&self, session_id: &str, started: DateTime<Utc>, lines: &[Value]) -> Result<PathBuf> {
+        let day_dir = self.sessions_dir()?.join(started.format("%Y/%m/%d").to_string());
+        fs::create_dir_all(&day_dir)?;
+
+        let path = day_dir.join(format!(
+            "rollout-{}-{}.jsonl",
+            started.format("%Y-%m-%dT%H-%M-%S"),
+            session_id
+        ));
+        let content: Vec<String> = lines.iter().map(Value::to_string).collect();
+        fs::write(&path, content.join("\n"))?;
+        Ok(path)
+    }
+
+    fn rewrite_session_id(value: &mut Value, old_id: &str, new_id: &str) {
+        let meta = if value.get("type").and_then(Value::as_str) == Some("session_meta") {
+            value.get_mut("payload")
+        } else {
+            Some(value)
+        };
+        if let Some(obj) = meta.and_then(Value::as_object_mut)
+            && obj.get("id").and_then(Value::as_str) == Some(old_id)
+        {
+            obj.insert("id".to_string(), Value::String(new_id.to_string()));
+        }
+    }
+}
+
+impl AgentAdapter for CodexAdapter {
+    fn kind(&self) -> AgentKind {
+        AgentKind::Codex
+    }
+
+    fn detect(&self, workspace: &Path) -> Result<bool> {
+        Ok(!self.list_sessions(workspace)?.is_empty())
+    }
+
+    fn list_sessions(&self, workspace: &Path) -> Result<Vec<AgentSession>> {
+        let workspace = workspace.to_string_lossy();
+        let mut sessions = Vec::new();
+
+        for path in self.rollouts()? {
+            let Some(meta) = Self::read_meta(&path)? else {
+                continue;
+            };
+            if !is_under(&meta.cwd, &workspace) {
+                continue;
+            }
+
+            let entry_count = fs::read_to_string(&path)?
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .count();
+
+            debug!("Found Codex rollout {} ({} entries)", path.display(), entry_count);
+            sessions.push(AgentSession {
+                agent: AgentKind::Codex,
+                session_id: meta.session_id,
+                session_file: path,
+                entry_count,
+                aux_files: Vec::new(),
+            });
+        }
+
+        Ok(sessions)
+    }
+
+    fn collect_files(&self, session: &AgentSession) -> Result<Vec<(String, PathBuf)>> {
+        let sessions_dir = self.sessions_dir()?;
+        let relative = session
+            .session_file
+            .strip_prefix(&sessions_dir)
+            .context("Codex rollout is outside the sessions directory")?
+            .to_str()
+            .context("Path contains invalid UTF-8")?
+            .replace('\\', "/");
+
+        Ok(vec![(
+            format!("{}/{}", CODEX_PACKAGE_DIR, relative),
+            session.session_file.clone(),
+        )])
+    }
+
+    fn restore(
+        &self,
+        info: &AgentSessionInfo,
+        package_dir: &Path,
+        target_dir: &Path,
+    ) -> Result<RestoredSession> {
+        let package_path = info
+            .files
+            .iter()
+            .find(|f| f.starts_with(CODEX_PACKAGE_DIR) && f.ends_with(".jsonl"))
+            .context("Codex rollout not listed in metadata")?;
+        let source = package_dir.join(package_path);
+        if !source.exists() {
+            anyhow::bail!("Codex rollout not found in package");
+        }
+
+        let relative = package_path
+            .strip_prefix(CODEX_PACKAGE_DIR)
+            .unwrap_or(package_path)
+            .trim_start_matches('/');
+        let mut dest = self.sessions_dir()?.join(relative);
+        let mut session_id = info.session_id.clone();
+
+        // Never overwrite a local rollout; give the copy a fresh id instead
+        if dest.exists() {
+            let new_id = uuid::Uuid::new_v4().to_string();
+            println!("⚠️  Codex session {} already exists locally", session_id);
+            println!("   Creating separate copy as {}", new_id);
+
+            let file_name = dest
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default()
+                .replace(&session_id, &new_id);
+            dest.set_file_name(file_name);
+            session_id = new_id;
+        }
+
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = fs::read_to_string(&source)?;
+        let mut output = Vec::new();
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            let mut value: Value = serde_json::from_str(line)
+                .with_context(|| format!("Failed to parse rollout line: {}", line))?;
+            Self::rewrite_session_id(&mut value, &info.session_id, &session_id);
+            output.push(serde_json::to_string(&value)?);
+        }
+        fs::write(&dest, output.join("\n"))?;
+
+        if let Some(source_workspace) = &info.source_workspace {
+            let abs_target = target_dir
+                .canonicalize()
+                .context("Failed to resolve target directory")?;
+            let rewritten = self.relocate_paths(&dest, Path::new(source_workspace), &abs_target)?;
+            debug!("Relocated {} paths in restored Codex rollout", rewritten);
+        }
+
+        println!("   Rollout path: {}", dest.display());
+
+        Ok(RestoredSession {
+            agent: AgentKind::Codex,
+            resume_hint: format!("codex resume {}", session_id),
+            session_id,
+            location: dest,
+        })
+    }
+
+    fn relocate_paths(&self, session_file: &Path, from: &Path, to: &Path) -> Result<usize> {
+        let from = from.to_string_lossy();
+        let to = to.to_string_lossy();
+        if from == to {
+            return Ok(0);
+        }
+
+        let content = fs::read_to_string(session_file)?;
+        let mut count = 0;
+        let mut output = Vec::new();
+
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            let mut value: Value = serde_json::from_str(line)?;
+            count += relocate_json_paths(&mut value, &from, &to);
+
+            // Tool call arguments are JSON encoded as a string
+            if let Some(arguments) = value.pointer_mut("/payload/arguments")
+                && let Some(mut args) = arguments.as_str().and_then(|a| serde_json::from_str::<Value>(a).ok())
+            {
+                let rewritten = relocate_json_paths(&mut args, &from, &to);
+                if rewritten > 0 {
+                    *arguments = Value::String(args.to_string());
+                    count += rewritten;
+                }
+            }
+
+            output.push(serde_json::to_string(&value)?);
+        }
+
+        fs::write(session_file, output.join("\n"))?;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::codex::CodexRolloutFixture;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detects_rollouts_by_cwd() {
+        let temp_dir = TempDir::new().unwrap();
+        let codex_home = temp_dir.path().join("codex");
+        let workspace = temp_dir.path().join("app");
+
+        CodexRolloutFixture::create(&codex_home, &workspace, "2025/01/01", "old-session", 1).unwrap();
+        CodexRolloutFixture::create(&codex_home, &workspace, "2025/01/02", "new-session", 2).unwrap();
+        CodexRolloutFixture::create(&codex_home, &temp_dir.path().join("other"), "2025/01/03", "unrelated", 1).unwrap();
+
+        let adapter = CodexAdapter::with_home(codex_home);
+        let sessions = adapter.list_sessions(&workspace).unwrap();
+        assert_eq!(sessions.len(), 2);
+
+        let latest = adapter.latest_session(&workspace).unwrap().unwrap();
+        assert_eq!(latest.session_id, "new-session");
+        assert_eq!(latest.entry_count, 9);
+
+        let files = adapter.collect_files(&latest).unwrap();
+        assert!(files[0].0.starts_with(".agentbeam/codex/2025/01/02/rollout-"));
+    }
+
+    #[test]
+    fn test_restore_rewrites_cwd() {
+        let temp_dir = TempDir::new().unwrap();
+        let sender_home = temp_dir.path().join("sender-codex");
+        let receiver_home = temp_dir.path().join("receiver-codex");
+        let sender_workspace = PathBuf::from("/home/alice/app");
+        let target = temp_dir.path().join("received");
+        fs::create_dir_all(&target).unwrap();
+
+        let fixture = CodexRolloutFixture::create(&sender_home, &sender_workspace, "2025/01/02", "abc", 1).unwrap();
+        let sender = CodexAdapter::with_home(sender_home);
+        let session = sender.latest_session(&sender_workspace).unwrap().unwrap();
+        let files = sender.collect_files(&session).unwrap();
+
+        // Lay the package out the way the receiver exports it
+        let package_dir = temp_dir.path().join("package");
+        let packaged = package_dir.join(&files[0].0);
+        fs::create_dir_all(packaged.parent().unwrap()).unwrap();
+        fs::copy(&fixture.path, &packaged).unwrap();
+
+        let info = session.info(&files, &sender_workspace);
+        let receiver = CodexAdapter::with_home(receiver_home.clone());
+        let restored = receiver.restore(&info, &package_dir, &target).unwrap();
+
+        assert!(restored.location.starts_with(receiver_home.join("sessions/2025/01/02")));
+        let abs_target = target.canonicalize().unwrap();
+        let sessions = receiver.list_sessions(&abs_target).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].session_id, "abc");
+
+        let content = fs::read_to_string(&restored.location).unwrap();
+        assert!(!content.contains("/home/alice/app"));
+    }
+}
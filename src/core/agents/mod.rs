@@ -0,0 +1,197 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+pub mod aider;
+pub mod claude;
+pub mod codex;
+
+pub use aider::AiderAdapter;
+pub use claude::ClaudeAdapter;
+pub use codex::CodexAdapter;
+
+/// Coding agents whose sessions AgentBeam knows how to beam
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AgentKind {
+    ClaudeCode,
+    Codex,
+    Aider,
+    /// An agent added by a newer AgentBeam version
+    #[serde(other)]
+    #[value(skip)]
+    Unknown,
+}
+
+impl fmt::Display for AgentKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AgentKind::ClaudeCode => "Claude Code",
+            AgentKind::Codex => "Codex CLI",
+            AgentKind::Aider => "Aider",
+            AgentKind::Unknown => "unknown agent",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A session found on the sender's machine
+#[derive(Debug, Clone)]
+pub struct AgentSession {
+    pub agent: AgentKind,
+    pub session_id: String,
+    /// The main transcript file
+    pub session_file: PathBuf,
+    pub entry_count: usize,
+    /// Additional state shipped with the session (todo lists, config, ...)
+    pub aux_files: Vec<PathBuf>,
+}
+
+/// Per-agent session description carried in the beam metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentSessionInfo {
+    pub agent: AgentKind,
+    pub session_id: String,
+    pub entry_count: usize,
+    /// Paths of this session's files inside the collection
+    pub files: Vec<String>,
+    /// Absolute workspace path on the sender, used to relocate paths on restore
+    #[serde(default)]
+    pub source_workspace: Option<String>,
+}
+
+/// Where a session ended up on the receiver
+#[derive(Debug, Clone)]
+pub struct RestoredSession {
+    pub agent: AgentKind,
+    pub session_id: String,
+    pub location: PathBuf,
+    /// Command the receiver runs to pick the session up
+    pub resume_hint: String,
+}
+
+/// Detection, packaging and restoration of one agent's sessions
+pub trait AgentAdapter: Send + Sync {
+    fn kind(&self) -> AgentKind;
+
+    /// What `AgentSession::entry_count` counts, for display
+    fn entry_unit(&self) -> &'static str {
+        "entries"
+    }
+
+    /// Whether the agent has any state for this workspace
+    fn detect(&self, workspace: &Path) -> Result<bool>;
+
+    /// Sessions for the workspace, oldest first
+    fn list_sessions(&self, workspace: &Path) -> Result<Vec<AgentSession>>;
+
+    /// Files to add to the collection as `(collection path, local path)`
+    fn collect_files(&self, session: &AgentSession) -> Result<Vec<(String, PathBuf)>>;
+
+    /// Restore a session from an exported package into the receiver's agent state
+    fn restore(
+        &self,
+        info: &AgentSessionInfo,
+        package_dir: &Path,
+        target_dir: &Path,
+    ) -> Result<RestoredSession>;
+
+    /// Rewrite absolute sender paths in a restored session file; returns the number of rewrites
+    fn relocate_paths(&self, session_file: &Path, from: &Path, to: &Path) -> Result<usize>;
+
+    /// The most recent session for the workspace, if any
+    fn latest_session(&self, workspace: &Path) -> Result<Option<AgentSession>> {
+        if !self.detect(workspace)? {
+            return Ok(None);
+        }
+        Ok(self.list_sessions(workspace)?.pop())
+    }
+}
+
+impl AgentSession {
+    pub fn info(&self, files: &[(String, PathBuf)], source_workspace: &Path) -> AgentSessionInfo {
+        AgentSessionInfo {
+            agent: self.agent,
+            session_id: self.session_id.clone(),
+            entry_count: self.entry_count,
+            files: files.iter().map(|(name, _)| name.clone()).collect(),
+            source_workspace: Some(source_workspace.to_string_lossy().to_string()),
+        }
+    }
+}
+
+/// Every adapter AgentBeam ships with
+pub fn adapters() -> Vec<Box<dyn AgentAdapter>> {
+    vec![
+        Box::new(ClaudeAdapter),
+        Box::new(CodexAdapter::default()),
+        Box::new(AiderAdapter),
+    ]
+}
+
+/// The adapter responsible for an agent kind
+pub fn adapter_for(kind: AgentKind) -> Option<Box<dyn AgentAdapter>> {
+    adapters().into_iter().find(|adapter| adapter.kind() == kind)
+}
+
+/// JSON keys that hold filesystem paths in agent transcripts
+const PATH_KEYS: &[&str] = &["cwd", "file_path", "notebook_path", "path", "workdir"];
+
+/// Replace a path prefix in every path-valued field of a JSON value
+pub fn relocate_json_paths(value: &mut Value, from: &str, to: &str) -> usize {
+    match value {
+        Value::Object(map) => {
+            let mut count = 0;
+            for (key, child) in map.iter_mut() {
+                if let Value::String(s) = child {
+                    if PATH_KEYS.contains(&key.as_str()) && is_under(s, from) {
+                        *s = format!("{}{}", to, &s[from.len()..]);
+                        count += 1;
+                    }
+                } else {
+                    count += relocate_json_paths(child, from, to);
+                }
+            }
+            count
+        }
+        Value::Array(items) => items
+            .iter_mut()
+            .map(|item| relocate_json_paths(item, from, to))
+            .sum(),
+        _ => 0,
+    }
+}
+
+/// Whether `path` equals `prefix` or lies below it
+pub(crate) fn is_under(path: &str, prefix: &str) -> bool {
+    path == prefix
+        || path
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relocate_json_paths() {
+        let mut value = serde_json::json!({
+            "cwd": "/home/alice/app",
+            "message": {"content": [{"input": {"file_path": "/home/alice/app/src/main.rs"}}]},
+            "other": "/home/alice/app",
+            "sibling": {"path": "/home/alice/application"},
+        });
+
+        let count = relocate_json_paths(&mut value, "/home/alice/app", "/Users/bob/app");
+
+        assert_eq!(count, 2);
+        assert_eq!(value["cwd"], "/Users/bob/app");
+        assert_eq!(value["message"]["content"][0]["input"]["file_path"], "/Users/bob/app/src/main.rs");
+        assert_eq!(value["other"], "/home/alice/app");
+        assert_eq!(value["sibling"]["path"], "/home/alice/application");
+    }
+}
@@ -0,0 +1,209 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use crate::core::config::ClaudeConfigPolicy;
+
+/// Personal settings: permission grants and MCP servers that never leave the machine by default
+const LOCAL_SETTINGS: &str = ".claude/settings.local.json";
+
+/// Shared project config that is beamed after sanitizing
+const SHARED_CONFIG_FILES: &[&str] = &[".claude/settings.json", ".mcp.json"];
+
+/// Replacement for MCP `env` values
+pub const REDACTED: &str = "[redacted by agentbeam]";
+
+/// One change made to Claude config before it is beamed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigChange {
+    pub path: String,
+    pub description: String,
+}
+
+pub struct ClaudeConfigFilter;
+
+impl ClaudeConfigFilter {
+    /// Apply `policy` to the Claude config files in `files`
+    ///
+    /// Sanitized copies are written to `work_dir` and replace the originals in the list.
+    pub fn apply(
+        files: &mut Vec<(String, PathBuf)>,
+        policy: ClaudeConfigPolicy,
+        work_dir: &Path,
+    ) -> Result<Vec<ConfigChange>> {
+        if policy == ClaudeConfigPolicy::Keep {
+            return Ok(Vec::new());
+        }
+
+        let mut changes = Vec::new();
+        let mut kept = Vec::with_capacity(files.len());
+        for (name, path) in files.drain(..) {
+            let is_local = matches_config(&name, LOCAL_SETTINGS);
+            let is_shared = SHARED_CONFIG_FILES.iter().any(|file| matches_config(&name, file));
+
+            if is_local || (is_shared && policy == ClaudeConfigPolicy::Exclude) {
+                changes.push(ConfigChange {
+                    description: "excluded".to_string(),
+                    path: name,
+                });
+                continue;
+            }
+            if !is_shared {
+                kept.push((name, path));
+                continue;
+            }
+
+            let Some(mut json) = fs::read_to_string(&path).ok().and_then(|c| serde_json::from_str::<Value>(&c).ok()) else {
+                changes.push(ConfigChange {
+                    description: "excluded (could not be parsed to sanitize it)".to_string(),
+                    path: name,
+                });
+                continue;
+            };
+
+            let file_changes = sanitize(&mut json);
+            if file_changes.is_empty() {
+                kept.push((name, path));
+                continue;
+            }
+
+            let sanitized = work_dir.join("claude-config").join(&name);
+            if let Some(parent) = sanitized.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&sanitized, serde_json::to_string_pretty(&json)? + "\n")?;
+            debug!("Sanitized {} into {}", name, sanitized.display());
+
+            changes.extend(file_changes.into_iter().map(|description| ConfigChange {
+                path: name.clone(),
+                description,
+            }));
+            kept.push((name, sanitized));
+        }
+
+        *files = kept;
+        Ok(changes)
+    }
+
+    /// Whether `policy` would exclude or change a version of `name` with `content`, e.g. one in git history
+    pub fn would_change(name: &str, content: &[u8], policy: ClaudeConfigPolicy) -> bool {
+        if policy == ClaudeConfigPolicy::Keep {
+            return false;
+        }
+        if matches_config(name, LOCAL_SETTINGS) {
+            return true;
+        }
+        if !SHARED_CONFIG_FILES.iter().any(|file| matches_config(name, file)) {
+            return false;
+        }
+        if policy == ClaudeConfigPolicy::Exclude {
+            return true;
+        }
+        match serde_json::from_slice::<Value>(content) {
+            Ok(mut json) => !sanitize(&mut json).is_empty(),
+            Err(_) => true,
+        }
+    }
+}
+
+/// Strip `permissions.allow` and redact MCP `env` values, describing each change
+fn sanitize(json: &mut Value) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    if let Some(permissions) = json.get_mut("permissions").and_then(Value::as_object_mut)
+        && let Some(allow) = permissions.remove("allow")
+    {
+        let rules = allow.as_array().map_or(0, Vec::len);
+        changes.push(format!(
+            "stripped permissions.allow ({} {})",
+            rules,
+            if rules == 1 { "rule" } else { "rules" }
+        ));
+    }
+
+    if let Some(servers) = json.get_mut("mcpServers").and_then(Value::as_object_mut) {
+        for (server, config) in servers.iter_mut() {
+            let Some(env) = config.get_mut("env").and_then(Value::as_object_mut) else {
+                continue;
+            };
+            let mut redacted: Vec<&str> = Vec::new();
+            for (key, value) in env.iter_mut() {
+                if *value != Value::String(REDACTED.to_string()) {
+                    *value = Value::String(REDACTED.to_string());
+                    redacted.push(key.as_str());
+                }
+            }
+            if !redacted.is_empty() {
+                changes.push(format!("redacted mcpServers.{}.env ({})", server, redacted.join(", ")));
+            }
+        }
+    }
+
+    changes
+}
+
+/// Whether collection path `name` is `file` at the root or in a nested project
+fn matches_config(name: &str, file: &str) -> bool {
+    name == file || name.ends_with(&format!("/{}", file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_strips_local_settings_permissions_and_mcp_env() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        fs::create_dir_all(workspace.join(".claude")).unwrap();
+        fs::write(workspace.join(".claude/settings.local.json"), r#"{"permissions":{"allow":["Bash(rm:*)"]}}"#).unwrap();
+        fs::write(
+            workspace.join(".claude/settings.json"),
+            r#"{"model":"opus","permissions":{"allow":["Bash(npm test)"],"deny":["Read(.env)"]}}"#,
+        )
+        .unwrap();
+        fs::write(
+            workspace.join(".mcp.json"),
+            r#"{"mcpServers":{"github":{"command":"gh-mcp","env":{"GITHUB_TOKEN":"ghp_secret"}}}}"#,
+        )
+        .unwrap();
+        fs::write(workspace.join("main.rs"), "fn main() {}\n").unwrap();
+
+        let mut files: Vec<(String, PathBuf)> = [".claude/settings.local.json", ".claude/settings.json", ".mcp.json", "main.rs"]
+            .iter()
+            .map(|name| (name.to_string(), workspace.join(name)))
+            .collect();
+        let work_dir = temp_dir.path().join("work");
+
+        let changes = ClaudeConfigFilter::apply(&mut files, ClaudeConfigPolicy::Strip, &work_dir).unwrap();
+        let summary: Vec<_> = changes.iter().map(|c| format!("{}: {}", c.path, c.description)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ".claude/settings.local.json: excluded",
+                ".claude/settings.json: stripped permissions.allow (1 rule)",
+                ".mcp.json: redacted mcpServers.github.env (GITHUB_TOKEN)",
+            ]
+        );
+
+        let names: Vec<_> = files.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec![".claude/settings.json", ".mcp.json", "main.rs"]);
+        let settings: Value = serde_json::from_str(&fs::read_to_string(&files[0].1).unwrap()).unwrap();
+        assert!(settings["permissions"].get("allow").is_none());
+        assert_eq!(settings["permissions"]["deny"][0], "Read(.env)");
+        let mcp = fs::read_to_string(&files[1].1).unwrap();
+        assert!(!mcp.contains("ghp_secret"));
+        assert_eq!(files[2].1, workspace.join("main.rs"));
+
+        // Committed versions are judged by content
+        let sanitized = fs::read(&files[1].1).unwrap();
+        let original = fs::read(workspace.join(".mcp.json")).unwrap();
+        assert!(ClaudeConfigFilter::would_change(".mcp.json", &original, ClaudeConfigPolicy::Strip));
+        assert!(!ClaudeConfigFilter::would_change(".mcp.json", &sanitized, ClaudeConfigPolicy::Strip));
+        assert!(!ClaudeConfigFilter::would_change(".mcp.json", &original, ClaudeConfigPolicy::Keep));
+        assert!(ClaudeConfigFilter::would_change(".claude/settings.local.json", b"{}", ClaudeConfigPolicy::Strip));
+    }
+}
@@ -0,0 +1,270 @@
+use anyhow::Result;
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::ValueEnum;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::model::{ContentBlock, EntryKind, SessionEntry, SessionTranscript};
+use super::render::result_text;
+use crate::core::agents::{AgentKind, CodexAdapter, RestoredSession};
+
+/// Longest tool output copied into a note before it is cut
+const MAX_NOTE_LEN: usize = 500;
+
+/// Agents a Claude Code session can be converted for
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvertTarget {
+    Codex,
+}
+
+impl ConvertTarget {
+    pub fn agent(&self) -> AgentKind {
+        match self {
+            ConvertTarget::Codex => AgentKind::Codex,
+        }
+    }
+}
+
+/// A converted session and how much of it had no direct equivalent
+#[derive(Debug, Clone)]
+pub struct ConvertedSession {
+    pub restored: RestoredSession,
+    pub items: usize,
+    /// Parts written as text notes because the target agent has no equivalent
+    pub notes: usize,
+}
+
+/// Convert a Claude Code transcript into `target`'s resumable format for a workspace at `cwd`
+pub fn convert_session(
+    transcript: &SessionTranscript,
+    target: ConvertTarget,
+    cwd: &Path,
+) -> Result<ConvertedSession> {
+    match target {
+        ConvertTarget::Codex => {
+            let session_id = uuid::Uuid::new_v4().to_string();
+            let rollout = CodexRollout::from_transcript(transcript, &session_id, &cwd.to_string_lossy());
+            let location = CodexAdapter::default().write_rollout(&session_id, rollout.started, &rollout.lines)?;
+
+            Ok(ConvertedSession {
+                restored: RestoredSession {
+                    agent: AgentKind::Codex,
+                    resume_hint: format!("codex resume {}", session_id),
+                    session_id,
+                    location,
+                },
+                items: rollout.lines.len(),
+                notes: rollout.notes,
+            })
+        }
+    }
+}
+
+/// Lines of a Codex CLI rollout built from a Claude Code transcript
+///
+/// Messages and Bash calls map onto Codex messages and `shell` calls. Other tools, compaction
+/// summaries and unsupported content become assistant notes; thinking is left out.
+#[derive(Debug, Clone)]
+pub struct CodexRollout {
+    pub started: DateTime<Utc>,
+    pub lines: Vec<Value>,
+    pub notes: usize,
+}
+
+impl CodexRollout {
+    pub fn from_transcript(transcript: &SessionTranscript, session_id: &str, cwd: &str) -> Self {
+        let started = transcript
+            .entries()
+            .find_map(SessionEntry::timestamp)
+            .unwrap_or_else(Utc::now);
+        let mut rollout = Self {
+            started,
+            lines: Vec::new(),
+            notes: 0,
+        };
+        let mut timestamp = format_timestamp(started);
+        let mut tool_names: HashMap<String, String> = HashMap::new();
+
+        rollout.push(&timestamp, "session_meta", json!({
+            "id": session_id,
+            "timestamp": timestamp,
+            "cwd": cwd,
+            "originator": "agentbeam",
+            "cli_version": env!("CARGO_PKG_VERSION"),
+            "instructions": null,
+        }));
+
+        for entry in transcript.entries() {
+            if let Some(ts) = entry.timestamp() {
+                timestamp = format_timestamp(ts);
+            }
+
+            if entry.is_compact_summary {
+                if let Some(text) = entry.text() {
+                    rollout.note(&timestamp, format!("Earlier conversation was summarized:\n{}", text));
+                }
+                continue;
+            }
+            if entry.is_meta || !matches!(entry.kind, EntryKind::User | EntryKind::Assistant) {
+                continue;
+            }
+
+            if entry.is_turn_start() {
+                rollout.push(&timestamp, "turn_context", json!({
+                    "cwd": cwd,
+                    "approval_policy": "on-request",
+                    "sandbox_policy": {"mode": "workspace-write"},
+                }));
+                if let Some(text) = entry.text() {
+                    rollout.message(&timestamp, "user", &text);
+                }
+                continue;
+            }
+
+            for block in entry.content_blocks() {
+                match block {
+                    ContentBlock::Text { text } if entry.kind == EntryKind::Assistant => {
+                        rollout.message(&timestamp, "assistant", text);
+                    }
+                    ContentBlock::Text { .. } | ContentBlock::Thinking { .. } => {}
+                    ContentBlock::ToolUse { id, name, input } => {
+                        tool_names.insert(id.clone(), name.clone());
+                        rollout.tool_call(&timestamp, id, name, input, cwd);
+                    }
+                    ContentBlock::ToolResult { tool_use_id, content, is_error } => {
+                        let name = tool_names.get(tool_use_id).map(String::as_str).unwrap_or("tool");
+                        let output = content.as_ref().map(result_text).unwrap_or_default();
+                        rollout.tool_result(&timestamp, tool_use_id, name, &output, is_error.unwrap_or(false));
+                    }
+                    ContentBlock::Other => {
+                        rollout.note(&timestamp, "Unsupported content omitted".to_string());
+                    }
+                }
+            }
+
+            // Plain-text assistant messages carry no blocks
+            if entry.kind == EntryKind::Assistant && entry.content_blocks().is_empty()
+                && let Some(text) = entry.text()
+            {
+                rollout.message(&timestamp, "assistant", &text);
+            }
+        }
+
+        rollout
+    }
+
+    fn push(&mut self, timestamp: &str, kind: &str, payload: Value) {
+        self.lines.push(json!({
+            "timestamp": timestamp,
+            "type": kind,
+            "payload": payload,
+        }));
+    }
+
+    fn message(&mut self, timestamp: &str, role: &str, text: &str) {
+        let (content_type, event) = match role {
+            "user" => ("input_text", json!({"type": "user_message", "message": text, "images": null})),
+            _ => ("output_text", json!({"type": "agent_message", "message": text})),
+        };
+        self.push(timestamp, "response_item", json!({
+            "type": "message",
+            "role": role,
+            "content": [{"type": content_type, "text": text}],
+        }));
+        self.push(timestamp, "event_msg", event);
+    }
+
+    fn note(&mut self, timestamp: &str, text: String) {
+        self.notes += 1;
+        self.message(timestamp, "assistant", &format!("[AgentBeam] {}", text));
+    }
+
+    fn tool_call(&mut self, timestamp: &str, id: &str, name: &str, input: &Value, cwd: &str) {
+        if name != "Bash" {
+            self.note(timestamp, format!("Claude Code called {} with {}", name, truncate(&input.to_string())));
+            return;
+        }
+
+        let command = input.get("command").and_then(Value::as_str).unwrap_or_default();
+        let mut arguments = json!({
+            "command": ["bash", "-lc", command],
+            "workdir": cwd,
+        });
+        if let Some(timeout) = input.get("timeout").and_then(Value::as_u64) {
+            arguments["timeout_ms"] = json!(timeout);
+        }
+
+        self.push(timestamp, "response_item", json!({
+            "type": "function_call",
+            "name": "shell",
+            "arguments": arguments.to_string(),
+            "call_id": id,
+        }));
+    }
+
+    fn tool_result(&mut self, timestamp: &str, id: &str, name: &str, output: &str, is_error: bool) {
+        if name != "Bash" {
+            let status = if is_error { "failed" } else { "returned" };
+            self.note(timestamp, format!("{} {}: {}", name, status, truncate(output)));
+            return;
+        }
+
+        let output = json!({
+            "output": output,
+            "metadata": {"exit_code": if is_error { 1 } else { 0 }, "duration_seconds": 0.0},
+        });
+        self.push(timestamp, "response_item", json!({
+            "type": "function_call_output",
+            "call_id": id,
+            "output": output.to_string(),
+        }));
+    }
+}
+
+fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_NOTE_LEN {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(MAX_NOTE_LEN).collect();
+    format!("{}…", cut)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION: &str = r#"{"type":"user","uuid":"a","timestamp":"2025-01-02T10:00:00Z","message":{"role":"user","content":"run the tests"}}
+{"type":"assistant","uuid":"b","parentUuid":"a","message":{"role":"assistant","content":[{"type":"thinking","thinking":"hmm"},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"cargo test"}},{"type":"tool_use","id":"t2","name":"Edit","input":{"file_path":"src/lib.rs"}}]}}
+{"type":"user","uuid":"c","parentUuid":"b","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"ok","is_error":false},{"type":"tool_result","tool_use_id":"t2","content":"edited"}]}}
+{"type":"assistant","uuid":"d","parentUuid":"c","message":{"role":"assistant","content":[{"type":"text","text":"All green."}]}}"#;
+
+    #[test]
+    fn test_codex_rollout_from_transcript() {
+        let transcript = SessionTranscript::parse(SESSION);
+        let rollout = CodexRollout::from_transcript(&transcript, "abc", "/work/app");
+
+        assert_eq!(rollout.lines[0]["type"], "session_meta");
+        assert_eq!(rollout.lines[0]["payload"]["id"], "abc");
+        assert_eq!(rollout.lines[0]["timestamp"], "2025-01-02T10:00:00.000Z");
+        assert_eq!(rollout.notes, 2);
+
+        let payloads: Vec<&Value> = rollout
+            .lines
+            .iter()
+            .filter(|l| l["type"] == "response_item")
+            .map(|l| &l["payload"])
+            .collect();
+        assert_eq!(payloads[0]["content"][0]["text"], "run the tests");
+        assert_eq!(payloads[1]["name"], "shell");
+        assert_eq!(payloads[1]["call_id"], "t1");
+        let arguments: Value = serde_json::from_str(payloads[1]["arguments"].as_str().unwrap()).unwrap();
+        assert_eq!(arguments["command"][2], "cargo test");
+        assert_eq!(payloads[3]["type"], "function_call_output");
+        assert_eq!(payloads.last().unwrap()["content"][0]["text"], "All green.");
+    }
+}
@@ -0,0 +1,276 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use super::model::{EntryKind, SessionTranscript};
+
+pub const HANDOFF_FILE: &str = "HANDOFF.md";
+
+/// Prompts and messages longer than this are cut in the handoff
+const MAX_TEXT_LEN: usize = 2000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoItem {
+    pub content: String,
+    pub status: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedCommand {
+    pub command: String,
+    pub error: String,
+}
+
+/// What the sender was doing, summarized offline from the session
+#[derive(Debug, Clone, Default)]
+pub struct Handoff {
+    pub session_id: String,
+    pub entry_count: usize,
+    pub first_prompt: Option<String>,
+    pub latest_prompt: Option<String>,
+    pub todos: Vec<TodoItem>,
+    pub edited_files: BTreeMap<String, usize>,
+    pub failed_commands: Vec<FailedCommand>,
+    pub last_assistant_message: Option<String>,
+}
+
+impl Handoff {
+    pub fn from_transcript(transcript: &SessionTranscript, session_id: &str) -> Self {
+        let mut handoff = Self {
+            session_id: session_id.to_string(),
+            entry_count: transcript.len(),
+            ..Default::default()
+        };
+
+        let cwd = transcript.entries().find_map(|e| e.cwd.clone());
+        let mut bash_commands: HashMap<String, String> = HashMap::new();
+
+        for entry in transcript.entries() {
+            if entry.is_turn_start() {
+                let text = entry.text();
+                if handoff.first_prompt.is_none() {
+                    handoff.first_prompt = text.clone();
+                }
+                handoff.latest_prompt = text;
+            }
+
+            if entry.kind == EntryKind::Assistant && let Some(text) = entry.text() {
+                handoff.last_assistant_message = Some(text);
+            }
+
+            for (id, name, input) in entry.tool_uses() {
+                match name {
+                    "Edit" | "MultiEdit" | "Write" => {
+                        if let Some(path) = input.get("file_path").and_then(Value::as_str) {
+                            let path = relative_to(path, cwd.as_deref());
+                            *handoff.edited_files.entry(path).or_default() += 1;
+                        }
+                    }
+                    "TodoWrite" => {
+                        if let Some(todos) = input.get("todos").and_then(Value::as_array) {
+                            handoff.todos = todos
+                                .iter()
+                                .filter_map(|t| {
+                                    Some(TodoItem {
+                                        content: t.get("content")?.as_str()?.to_string(),
+                                        status: t
+                                            .get("status")
+                                            .and_then(Value::as_str)
+                                            .unwrap_or("pending")
+                                            .to_string(),
+                                    })
+                                })
+                                .collect();
+                        }
+                    }
+                    "Bash" => {
+                        if let Some(command) = input.get("command").and_then(Value::as_str) {
+                            bash_commands.insert(id.to_string(), command.to_string());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            for (tool_use_id, content, is_error) in entry.tool_results() {
+                if !is_error {
+                    continue;
+                }
+                if let Some(command) = bash_commands.get(tool_use_id) {
+                    handoff.failed_commands.push(FailedCommand {
+                        command: command.clone(),
+                        error: content.map(first_line).unwrap_or_default(),
+                    });
+                }
+            }
+        }
+
+        handoff
+    }
+
+    pub fn to_markdown(&self, workspace_name: &str) -> String {
+        let mut md = String::new();
+        let _ = writeln!(md, "# Handoff: {}", workspace_name);
+        let _ = writeln!(md);
+        let _ = writeln!(
+            md,
+            "_Generated by AgentBeam from Claude Code session `{}` ({} entries)._",
+            self.session_id, self.entry_count
+        );
+
+        section(&mut md, "First prompt");
+        quote(&mut md, self.first_prompt.as_deref());
+
+        section(&mut md, "Latest prompt");
+        quote(&mut md, self.latest_prompt.as_deref());
+
+        section(&mut md, "Todo list");
+        if self.todos.is_empty() {
+            let _ = writeln!(md, "_No todo list recorded._");
+        }
+        for todo in &self.todos {
+            let (check, suffix) = match todo.status.as_str() {
+                "completed" => ("x", ""),
+                "in_progress" => (" ", " _(in progress)_"),
+                _ => (" ", ""),
+            };
+            let _ = writeln!(md, "- [{}] {}{}", check, todo.content, suffix);
+        }
+
+        section(&mut md, "Files edited");
+        if self.edited_files.is_empty() {
+            let _ = writeln!(md, "_No files edited._");
+        } else {
+            let _ = writeln!(md, "| File | Edits |");
+            let _ = writeln!(md, "| --- | ---: |");
+            for (path, count) in &self.edited_files {
+                let _ = writeln!(md, "| `{}` | {} |", path, count);
+            }
+        }
+
+        section(&mut md, "Failing commands");
+        if self.failed_commands.is_empty() {
+            let _ = writeln!(md, "_No failing commands seen._");
+        }
+        for failed in &self.failed_commands {
+            let _ = writeln!(md, "- `{}`", failed.command.replace('\n', " "));
+            if !failed.error.is_empty() {
+                let _ = writeln!(md, "  - {}", failed.error);
+            }
+        }
+
+        section(&mut md, "Last assistant message");
+        quote(&mut md, self.last_assistant_message.as_deref());
+
+        md
+    }
+}
+
+/// Write HANDOFF.md into a received workspace
+///
+/// Prefers the copy the sender reviewed and attached; otherwise summarizes the session locally.
+/// An existing HANDOFF.md from the workspace itself is left alone.
+pub fn write_received_handoff(
+    target_dir: &Path,
+    session_source: &Path,
+    session_id: &str,
+    workspace_name: &str,
+) -> Result<PathBuf> {
+    let attached = target_dir.join(".agentbeam").join(HANDOFF_FILE);
+    let content = if attached.exists() {
+        std::fs::read_to_string(&attached)?
+    } else {
+        let transcript = SessionTranscript::load(session_source)?;
+        Handoff::from_transcript(&transcript, session_id).to_markdown(workspace_name)
+    };
+
+    let dest = target_dir.join(HANDOFF_FILE);
+    let dest = if dest.exists() { attached } else { dest };
+    std::fs::write(&dest, content)?;
+    Ok(dest)
+}
+
+fn section(md: &mut String, title: &str) {
+    let _ = writeln!(md);
+    let _ = writeln!(md, "## {}", title);
+    let _ = writeln!(md);
+}
+
+fn quote(md: &mut String, text: Option<&str>) {
+    match text {
+        Some(text) => {
+            for line in truncate(text).lines() {
+                let _ = writeln!(md, "> {}", line);
+            }
+        }
+        None => {
+            let _ = writeln!(md, "_None._");
+        }
+    }
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_TEXT_LEN {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(MAX_TEXT_LEN).collect();
+    format!("{}…", cut)
+}
+
+fn first_line(content: &Value) -> String {
+    let text = match content {
+        Value::String(s) => s.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|b| b.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => other.to_string(),
+    };
+    text.lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// `path` relative to `cwd` when it lies inside it, compared by path component
+fn relative_to(path: &str, cwd: Option<&str>) -> String {
+    cwd.and_then(|cwd| Path::new(path).strip_prefix(cwd).ok())
+        .map(|rel| rel.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION: &str = r#"{"type":"user","uuid":"a","cwd":"/work/app","message":{"role":"user","content":"add a login page"}}
+{"type":"assistant","uuid":"b","parentUuid":"a","message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Write","input":{"file_path":"/work/app/src/login.rs","content":""}},{"type":"tool_use","id":"t2","name":"Bash","input":{"command":"cargo test"}},{"type":"tool_use","id":"t3","name":"TodoWrite","input":{"todos":[{"content":"Write login page","status":"completed"},{"content":"Fix tests","status":"in_progress"}]}}]}}
+{"type":"user","uuid":"c","parentUuid":"b","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t2","content":"error[E0425]: cannot find value\nmore","is_error":true}]}}
+{"type":"user","uuid":"d","parentUuid":"c","message":{"role":"user","content":"now fix the tests"}}
+{"type":"assistant","uuid":"e","parentUuid":"d","message":{"role":"assistant","content":[{"type":"text","text":"Tests are fixed."}]}}"#;
+
+    #[test]
+    fn test_handoff_from_transcript() {
+        let transcript = SessionTranscript::parse(SESSION);
+        let handoff = Handoff::from_transcript(&transcript, "abc");
+
+        assert_eq!(handoff.first_prompt.as_deref(), Some("add a login page"));
+        assert_eq!(handoff.latest_prompt.as_deref(), Some("now fix the tests"));
+        assert_eq!(handoff.edited_files.get("src/login.rs"), Some(&1));
+        assert_eq!(handoff.todos.len(), 2);
+        assert_eq!(handoff.failed_commands[0].command, "cargo test");
+        assert_eq!(handoff.failed_commands[0].error, "error[E0425]: cannot find value");
+        assert_eq!(handoff.last_assistant_message.as_deref(), Some("Tests are fixed."));
+
+        let md = handoff.to_markdown("app");
+        assert!(md.contains("- [x] Write login page"));
+        assert!(md.contains("| `src/login.rs` | 1 |"));
+
+        assert_eq!(relative_to("/work/app/src/x.rs", Some("/work/app/")), "src/x.rs");
+        assert_eq!(relative_to("/work/app2/x.rs", Some("/work/app")), "/work/app2/x.rs");
+    }
+}
@@ -0,0 +1,680 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+use tracing::{debug, info, trace, warn};
+
+use crate::core::agents::{
+    AgentAdapter, AgentKind, AgentSession, AgentSessionInfo, ClaudeAdapter, RestoredSession,
+};
+use crate::core::git_layout::RepoLayout;
+use crate::core::git_repo::GitRepo;
+
+pub mod convert;
+pub mod handoff;
+pub mod model;
+pub mod render;
+pub mod stats;
+pub mod touched;
+
+pub use convert::{convert_session, ConvertTarget, ConvertedSession};
+pub use handoff::{Handoff, HANDOFF_FILE};
+pub use model::{ContentBlock, EntryKind, MessageContent, SessionEntry, SessionTranscript};
+pub use render::{RenderFormat, RenderOptions, SessionRenderer};
+pub use stats::{SessionStats, SessionValidation, TokenUsage};
+pub use touched::TouchedFile;
+
+#[derive(Debug, Clone)]
+pub struct ClaudeSession {
+    pub session_file: PathBuf,
+    pub session_id: String,
+    pub project_slug: String,
+    pub entry_count: usize,
+    pub trimmed_entry_count: usize,
+    pub stats: SessionStats,
+    pub validation: SessionValidation,
+    /// Todo list files Claude Code keeps for this session under ~/.claude/todos
+    pub todo_files: Vec<PathBuf>,
+    /// HANDOFF.md generated by the sender for review before beaming
+    pub handoff_file: Option<PathBuf>,
+}
+
+impl ClaudeSession {
+    /// The agent-neutral view of this session
+    pub fn agent_session(&self) -> AgentSession {
+        AgentSession {
+            agent: AgentKind::ClaudeCode,
+            session_id: self.session_id.clone(),
+            session_file: self.session_file.clone(),
+            entry_count: self.entry_count,
+            aux_files: self.todo_files.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClaudeContext {
+    pub session: Option<ClaudeSession>,
+    pub git_branch: String,
+    pub git_has_changes: bool,
+    pub git_remote_url: Option<String>,
+    pub git_head: Option<String>,
+    pub git_stash_count: usize,
+    pub git_layout: RepoLayout,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClaudeSessionInfo {
+    pub original_session_id: String,
+    pub project_slug: String,
+    pub entry_count: usize,
+    /// Number of entries dropped from the start of the session before beaming
+    #[serde(default)]
+    pub trimmed_entry_count: usize,
+}
+
+/// Where to cut a session before beaming it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionCutoff {
+    /// Keep everything from the given user turn (1-based) onwards
+    Turn(usize),
+    /// Keep everything from the first turn at or after the timestamp
+    Timestamp(DateTime<Utc>),
+    /// Keep everything from the most recent compaction boundary
+    LastCompact,
+}
+
+impl FromStr for SessionCutoff {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Ok(turn) = s.parse::<usize>() {
+            if turn == 0 {
+                anyhow::bail!("Turn numbers start at 1");
+            }
+            return Ok(SessionCutoff::Turn(turn));
+        }
+
+        let timestamp = DateTime::parse_from_rfc3339(s)
+            .with_context(|| format!("Expected a turn number or RFC 3339 timestamp, got '{}'", s))?;
+        Ok(SessionCutoff::Timestamp(timestamp.with_timezone(&Utc)))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitContext {
+    pub branch: String,
+    pub has_uncommitted_changes: bool,
+    pub remote_url: Option<String>,
+    #[serde(default)]
+    pub head_commit: Option<String>,
+    /// Stash entries, which are not beamed
+    #[serde(default)]
+    pub stash_count: usize,
+    /// Worktree, submodules and LFS paths
+    #[serde(default)]
+    pub layout: RepoLayout,
+}
+
+impl ClaudeContext {
+    /// Detect Claude session and git context for a workspace
+    pub async fn detect(workspace: &Path) -> Result<Self> {
+        debug!("Detecting Claude context for: {}", workspace.display());
+        
+        // Get git context
+        let git = Self::get_git_state(workspace)?;
+        
+        // Detect Claude session
+        let session = Self::detect_session(workspace)?;
+        
+        if let Some(ref s) = session {
+            info!("Found Claude session: {} ({} entries)", s.session_id, s.entry_count);
+        } else {
+            debug!("No Claude session found for workspace");
+        }
+        
+        Ok(Self {
+            session,
+            git_branch: git.branch,
+            git_has_changes: git.has_uncommitted_changes,
+            git_remote_url: git.remote_url,
+            git_head: git.head_commit,
+            git_stash_count: git.stash_count,
+            git_layout: git.layout,
+        })
+    }
+    
+    /// Add Claude session file and its auxiliary state to the collection files list
+    ///
+    /// Returns the session's entry for the metadata's agent session list.
+    pub fn add_to_collection(
+        &self,
+        files: &mut Vec<(String, PathBuf)>,
+        workspace: &Path,
+    ) -> Result<Option<AgentSessionInfo>> {
+        let Some(ref session) = self.session else {
+            return Ok(None);
+        };
+        
+        let agent_session = session.agent_session();
+        let mut session_files = ClaudeAdapter.collect_files(&agent_session)?;
+        if let Some(ref handoff_file) = session.handoff_file {
+            session_files.push((format!(".agentbeam/{}", HANDOFF_FILE), handoff_file.clone()));
+        }
+        
+        let info = agent_session.info(&session_files, workspace);
+        files.extend(session_files);
+        Ok(Some(info))
+    }
+    
+    /// Summarize the session into a HANDOFF.md in `work_dir` so the sender can review it
+    pub fn generate_handoff(&mut self, workspace_name: &str, work_dir: &Path) -> Result<Option<PathBuf>> {
+        let Some(ref mut session) = self.session else {
+            return Ok(None);
+        };
+        
+        let transcript = SessionTranscript::load(&session.session_file)?;
+        let markdown = Handoff::from_transcript(&transcript, &session.session_id)
+            .to_markdown(workspace_name);
+        
+        fs::create_dir_all(work_dir)?;
+        let handoff_file = work_dir.join(HANDOFF_FILE);
+        fs::write(&handoff_file, markdown)?;
+        
+        session.handoff_file = Some(handoff_file.clone());
+        Ok(Some(handoff_file))
+    }
+    
+    /// Workspace files the agent modified in the session that will be beamed
+    pub fn agent_touched_files(&self, workspace: &Path) -> Result<Vec<TouchedFile>> {
+        let Some(ref session) = self.session else {
+            return Ok(Vec::new());
+        };
+        
+        let transcript = SessionTranscript::load(&session.session_file)?;
+        Ok(TouchedFile::from_transcript(&transcript, workspace))
+    }
+    
+    /// Git context as recorded in the beam metadata
+    pub fn git_context(&self) -> GitContext {
+        GitContext {
+            branch: self.git_branch.clone(),
+            has_uncommitted_changes: self.git_has_changes,
+            remote_url: self.git_remote_url.clone(),
+            head_commit: self.git_head.clone(),
+            stash_count: self.git_stash_count,
+            layout: self.git_layout.clone(),
+        }
+    }
+    
+    /// Trim the session to the given cutoff, writing the trimmed copy into `work_dir`
+    pub fn trim_session(&mut self, cutoff: &SessionCutoff, work_dir: &Path) -> Result<usize> {
+        let Some(ref mut session) = self.session else {
+            return Ok(0);
+        };
+        
+        let transcript = SessionTranscript::load(&session.session_file)?;
+        if !transcript.malformed.is_empty() {
+            anyhow::bail!(
+                "Cannot trim session with {} malformed lines",
+                transcript.malformed.len()
+            );
+        }
+        
+        let Some(cut) = Self::find_cut_index(&transcript, cutoff)? else {
+            debug!("Session cutoff keeps every entry");
+            return Ok(0);
+        };
+        
+        let trimmed = Self::trim_entries(transcript, cut, &session.session_id);
+        let dropped = cut;
+        
+        fs::create_dir_all(work_dir)?;
+        let trimmed_file = work_dir.join(format!("{}.jsonl", session.session_id));
+        let lines = trimmed
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<Vec<_>, _>>()?;
+        fs::write(&trimmed_file, lines.join("\n"))?;
+        
+        info!("Trimmed {} entries from session {}", dropped, session.session_id);
+        
+        let trimmed_transcript = SessionTranscript::load(&trimmed_file)?;
+        session.validation = SessionValidation::check(&trimmed_transcript);
+        
+        // The prepended summary is ours, not part of the conversation being counted
+        let mut conversation = trimmed_transcript;
+        conversation.lines.remove(0);
+        session.session_file = trimmed_file;
+        session.entry_count = conversation.len();
+        session.trimmed_entry_count = dropped;
+        session.stats = SessionStats::from_transcript(&conversation);
+        
+        Ok(dropped)
+    }
+    
+    /// Find the index of the first entry to keep, or `None` if nothing is dropped
+    fn find_cut_index(transcript: &SessionTranscript, cutoff: &SessionCutoff) -> Result<Option<usize>> {
+        let entries: Vec<&SessionEntry> = transcript.entries().collect();
+        let cut = match cutoff {
+            SessionCutoff::Turn(turn) => entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.is_turn_start())
+                .nth(turn - 1)
+                .map(|(i, _)| i)
+                .with_context(|| format!("Session has fewer than {} turns", turn))?,
+            SessionCutoff::Timestamp(since) => entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.is_turn_start())
+                .find(|(_, entry)| entry.timestamp().is_some_and(|ts| ts >= *since))
+                .map(|(i, _)| i)
+                .with_context(|| format!("No turns found at or after {}", since.to_rfc3339()))?,
+            SessionCutoff::LastCompact => entries
+                .iter()
+                .rposition(|entry| entry.is_compact_boundary())
+                .context("Session has no compaction boundary")?,
+        };
+        
+        Ok((cut > 0).then_some(cut))
+    }
+    
+    /// Drop entries before `cut`, re-root the remaining chain and prepend a summary
+    fn trim_entries(transcript: SessionTranscript, cut: usize, session_id: &str) -> Vec<Value> {
+        let mut lines = transcript.lines;
+        let kept = lines.split_off(cut);
+        let dropped_uuids: HashSet<String> = lines
+            .iter()
+            .filter_map(|l| l.entry.uuid.clone())
+            .collect();
+        let dropped_turns = lines.iter().filter(|l| l.entry.is_turn_start()).count();
+        
+        // Summaries describing the dropped part no longer apply
+        let kept = kept.into_iter().filter(|l| {
+            l.entry.kind != EntryKind::Summary
+                || l.entry.leaf_uuid
+                    .as_ref()
+                    .is_none_or(|leaf| !dropped_uuids.contains(leaf))
+        });
+        
+        let mut previous_uuid: Option<String> = None;
+        let mut rewritten = Vec::new();
+        for line in kept {
+            let mut raw = line.raw;
+            if let Some(obj) = raw.as_object_mut() {
+                let dangling = line.entry.parent_uuid
+                    .as_ref()
+                    .is_some_and(|p| dropped_uuids.contains(p));
+                
+                if line.entry.uuid.is_some() && previous_uuid.is_none() {
+                    // First kept entry becomes the new root
+                    obj.insert("parentUuid".to_string(), Value::Null);
+                } else if dangling {
+                    obj.insert(
+                        "parentUuid".to_string(),
+                        previous_uuid.clone().map_or(Value::Null, Value::String),
+                    );
+                }
+            }
+            
+            if let Some(uuid) = line.entry.uuid {
+                previous_uuid = Some(uuid);
+            }
+            rewritten.push(raw);
+        }
+        
+        let summary = serde_json::json!({
+            "type": "summary",
+            "summary": format!(
+                "Trimmed by AgentBeam: {} earlier entries ({} turns) were dropped before beaming session {}",
+                cut,
+                dropped_turns,
+                session_id,
+            ),
+            "leafUuid": previous_uuid,
+        });
+        
+        let mut trimmed = Vec::with_capacity(rewritten.len() + 1);
+        trimmed.push(summary);
+        trimmed.extend(rewritten);
+        trimmed
+    }
+    
+    /// Restore Claude session on the receiver side
+    pub fn restore(
+        target_dir: &Path,
+        claude_info: &ClaudeSessionInfo,
+        session_source: &Path,
+    ) -> Result<RestoredSession> {
+        info!("Restoring Claude session for receiver");
+        
+        // Generate project slug for receiver's absolute path
+        // Canonicalize to normalize the path (remove ./ and ../ components)
+        let abs_target = if target_dir.is_absolute() {
+            target_dir.canonicalize()?
+        } else {
+            std::env::current_dir()?.join(target_dir).canonicalize()?
+        };
+        let receiver_slug = Self::path_to_slug(&abs_target);
+        let claude_dir = Self::claude_dir()?;
+        let claude_project_dir = claude_dir.join("projects").join(&receiver_slug);
+        
+        // Create directory if needed
+        fs::create_dir_all(&claude_project_dir)?;
+        
+        // Check if the original session ID already exists
+        let original_session_file = claude_project_dir.join(format!("{}.jsonl", claude_info.original_session_id));
+        
+        let (new_session_id, session_dest) = if original_session_file.exists() {
+            // Collision detected - append -agentbeam to avoid overwriting
+            println!("⚠️  Session ID {} already exists locally", claude_info.original_session_id);
+            println!("   Creating separate copy with -agentbeam suffix");
+            
+            let new_id = format!("{}-agentbeam", claude_info.original_session_id);
+            let dest = claude_project_dir.join(format!("{}.jsonl", new_id));
+            (new_id, dest)
+        } else {
+            // Safe to use a new UUID
+            let new_id = uuid::Uuid::new_v4().to_string();
+            let dest = claude_project_dir.join(format!("{}.jsonl", new_id));
+            (new_id, dest)
+        };
+        
+        // Copy session with updated IDs
+        Self::copy_session_with_new_id(session_source, &session_dest, &new_session_id)?;
+        
+        // Restore todo lists shipped alongside the session
+        if let Some(todos_source) = session_source.parent().map(|p| p.join("todos")) && todos_source.is_dir() {
+            let todos_dir = claude_dir.join("todos");
+            fs::create_dir_all(&todos_dir)?;
+            for entry in fs::read_dir(&todos_source)?.filter_map(Result::ok) {
+                let name = entry.file_name().to_string_lossy()
+                    .replace(&claude_info.original_session_id, &new_session_id);
+                fs::copy(entry.path(), todos_dir.join(&name))?;
+                trace!("Restored todo file {}", name);
+            }
+        }
+        
+        info!("Claude session restored to: {}", session_dest.display());
+        
+        println!("   Session path: {}", session_dest.display());
+        
+        Ok(RestoredSession {
+            agent: AgentKind::ClaudeCode,
+            session_id: new_session_id,
+            location: session_dest,
+            resume_hint: "claude --continue".to_string(),
+        })
+    }
+    
+    /// Detect Claude session for a workspace
+    fn detect_session(workspace: &Path) -> Result<Option<ClaudeSession>> {
+        let claude_dir = Self::project_dir(workspace)?;
+        
+        if !claude_dir.exists() {
+            trace!("Claude project directory does not exist: {}", claude_dir.display());
+            return Ok(None);
+        }
+        
+        match Self::list_session_files(&claude_dir)?.pop() {
+            Some(path) => Ok(Some(Self::load_session(workspace, path)?)),
+            None => {
+                trace!("No session files found in Claude project directory");
+                Ok(None)
+            }
+        }
+    }
+    
+    /// Claude's config directory: `$CLAUDE_CONFIG_DIR`, or `~/.claude`
+    pub fn claude_dir() -> Result<PathBuf> {
+        if let Some(dir) = std::env::var_os("CLAUDE_CONFIG_DIR") {
+            return Ok(PathBuf::from(dir));
+        }
+        let home = dirs::home_dir().context("Failed to get home directory")?;
+        Ok(home.join(".claude"))
+    }
+    
+    /// Claude's project directory for a workspace
+    pub fn project_dir(workspace: &Path) -> Result<PathBuf> {
+        Ok(Self::claude_dir()?.join("projects").join(Self::path_to_slug(workspace)))
+    }
+    
+    /// Load a session file with its statistics and auxiliary state
+    pub fn load_session(workspace: &Path, path: PathBuf) -> Result<ClaudeSession> {
+        let claude_dir = Self::claude_dir()?;
+        let session_id = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        
+        let transcript = SessionTranscript::load(&path)?;
+        let todo_files = Self::find_todo_files(&claude_dir, &session_id)?;
+        let validation = SessionValidation::check(&transcript);
+        if !validation.is_valid() {
+            warn!("Session {} has problems: {}", session_id, validation.problems().join("; "));
+        }
+        
+        Ok(ClaudeSession {
+            session_file: path,
+            session_id,
+            project_slug: Self::path_to_slug(workspace),
+            entry_count: transcript.len(),
+            trimmed_entry_count: 0,
+            stats: SessionStats::from_transcript(&transcript),
+            validation,
+            todo_files,
+            handoff_file: None,
+        })
+    }
+    
+    /// Convert a file path to Claude's project slug format
+    pub fn path_to_slug(path: &Path) -> String {
+        path.to_string_lossy()
+            .chars()
+            .map(|c| if c == '/' { '-' } else { c })
+            .collect()
+    }
+    
+    /// Session files in a project directory, least recently modified first
+    pub fn list_session_files(claude_dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut sessions: Vec<_> = fs::read_dir(claude_dir)?
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                entry.path()
+                    .extension()
+                    .map(|ext| ext == "jsonl")
+                    .unwrap_or(false)
+            })
+            .collect();
+        
+        // Sort by modification time
+        sessions.sort_by_key(|entry| {
+            entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH)
+        });
+        
+        Ok(sessions.into_iter().map(|e| e.path()).collect())
+    }
+    
+    /// Find todo list files belonging to a session
+    fn find_todo_files(claude_dir: &Path, session_id: &str) -> Result<Vec<PathBuf>> {
+        let todos_dir = claude_dir.join("todos");
+        if !todos_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        
+        let mut todo_files: Vec<PathBuf> = fs::read_dir(&todos_dir)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(session_id) && n.ends_with(".json"))
+            })
+            .collect();
+        todo_files.sort();
+        
+        Ok(todo_files)
+    }
+    
+    /// Get git state for a workspace
+    pub fn get_git_state(workspace: &Path) -> Result<GitContext> {
+        // `.git` may be a file in worktrees and submodules; discovery follows it
+        let Some(mut repo) = GitRepo::discover(workspace)? else {
+            debug!("Workspace is not a git repository");
+            return Ok(GitContext {
+                branch: "main".to_string(),
+                has_uncommitted_changes: false,
+                remote_url: None,
+                head_commit: None,
+                stash_count: 0,
+                layout: RepoLayout::default(),
+            });
+        };
+        
+        let state = repo.state()?;
+        let remote_url = state
+            .remotes
+            .iter()
+            .find(|(name, _)| name == "origin")
+            .and_then(|(_, url)| url.clone());
+        
+        Ok(GitContext {
+            // Detached HEAD has no branch name
+            branch: state.branch.unwrap_or_default(),
+            has_uncommitted_changes: state.has_uncommitted_changes,
+            remote_url,
+            head_commit: state.head,
+            stash_count: state.stash_count,
+            layout: RepoLayout::inspect(workspace)?,
+        })
+    }
+    
+    /// Copy a session file with updated session IDs
+    fn copy_session_with_new_id(
+        source: &Path,
+        dest: &Path,
+        new_session_id: &str,
+    ) -> Result<()> {
+        let mut transcript = SessionTranscript::load(source)?;
+        if let Some(first) = transcript.malformed.first() {
+            println!("⚠️  Keeping {} malformed session lines as they are", transcript.malformed.len());
+            warn!("First malformed session line {}: {}", first.line_number, first.error);
+        }
+        
+        for line in &mut transcript.lines {
+            // Update sessionId field if present
+            if line.entry.session_id.is_some() && let Some(obj) = line.raw.as_object_mut() {
+                obj.insert(
+                    "sessionId".to_string(),
+                    serde_json::json!(new_session_id),
+                );
+            }
+        }
+        
+        fs::write(dest, transcript.to_jsonl()?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(uuid: &str, parent: Option<&str>, kind: &str, content: Value) -> String {
+        serde_json::json!({
+            "type": kind,
+            "uuid": uuid,
+            "parentUuid": parent,
+            "sessionId": "original",
+            "timestamp": "2025-01-01T10:00:00Z",
+            "message": { "role": kind, "content": content },
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_trim_session_reroots_chain() {
+        let temp_dir = TempDir::new().unwrap();
+        let session_file = temp_dir.path().join("original.jsonl");
+        let lines = [
+            entry("a", None, "user", serde_json::json!("first prompt")),
+            entry("b", Some("a"), "assistant", serde_json::json!([{"type": "text", "text": "ok"}])),
+            entry("c", Some("b"), "user", serde_json::json!("second prompt")),
+            entry("d", Some("c"), "assistant", serde_json::json!([{"type": "text", "text": "done"}])),
+        ];
+        fs::write(&session_file, lines.join("\n")).unwrap();
+
+        let mut context = ClaudeContext {
+            session: Some(ClaudeSession {
+                session_file,
+                session_id: "original".to_string(),
+                project_slug: "-tmp-project".to_string(),
+                entry_count: 4,
+                trimmed_entry_count: 0,
+                stats: SessionStats::default(),
+                validation: SessionValidation::default(),
+                todo_files: Vec::new(),
+                handoff_file: None,
+            }),
+            git_branch: "main".to_string(),
+            git_has_changes: false,
+            git_remote_url: None,
+            git_head: None,
+            git_stash_count: 0,
+            git_layout: RepoLayout::default(),
+        };
+
+        let dropped = context
+            .trim_session(&SessionCutoff::Turn(2), &temp_dir.path().join("trimmed"))
+            .unwrap();
+        assert_eq!(dropped, 2);
+
+        let session = context.session.unwrap();
+        assert_eq!(session.trimmed_entry_count, 2);
+        assert_eq!(session.entry_count, 2);
+        assert_eq!(session.stats.entries, 2);
+
+        let content = fs::read_to_string(&session.session_file).unwrap();
+        let entries: Vec<Value> = content.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(entries[0]["type"], "summary");
+        assert_eq!(entries[0]["leafUuid"], "d");
+        assert_eq!(entries[1]["uuid"], "c");
+        assert!(entries[1]["parentUuid"].is_null());
+        assert_eq!(entries[2]["parentUuid"], "c");
+    }
+
+    #[test]
+    fn test_copy_session_keeps_malformed_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("original.jsonl");
+        let dest = temp_dir.path().join("copy.jsonl");
+        let lines = [
+            entry("a", None, "user", serde_json::json!("first prompt")),
+            "{\"type\": \"assistant\", \"uuid\": \"b\"".to_string(),
+            entry("c", Some("a"), "user", serde_json::json!("second prompt")),
+        ];
+        fs::write(&source, lines.join("\n")).unwrap();
+
+        ClaudeContext::copy_session_with_new_id(&source, &dest, "copy").unwrap();
+
+        let content = fs::read_to_string(&dest).unwrap();
+        let copied: Vec<&str> = content.lines().collect();
+        assert_eq!(copied.len(), 3);
+        assert_eq!(copied[1], lines[1]);
+        for line in [copied[0], copied[2]] {
+            let entry: Value = serde_json::from_str(line).unwrap();
+            assert_eq!(entry["sessionId"], "copy");
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::Path;
+use tracing::debug;
+
+/// Top-level kind of a session JSONL entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    User,
+    Assistant,
+    Summary,
+    System,
+    #[serde(other)]
+    Unknown,
+}
+
+/// A single line of a Claude Code session file
+///
+/// Only the fields AgentBeam reasons about are typed; everything else is kept in `extra`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionEntry {
+    #[serde(rename = "type")]
+    pub kind: EntryKind,
+    #[serde(default)]
+    pub uuid: Option<String>,
+    #[serde(default)]
+    pub parent_uuid: Option<String>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub timestamp: Option<String>,
+    #[serde(default)]
+    pub cwd: Option<String>,
+    #[serde(default)]
+    pub git_branch: Option<String>,
+    #[serde(default)]
+    pub is_sidechain: bool,
+    #[serde(default)]
+    pub is_meta: bool,
+    #[serde(default)]
+    pub is_compact_summary: bool,
+    #[serde(default)]
+    pub subtype: Option<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub leaf_uuid: Option<String>,
+    #[serde(default)]
+    pub message: Option<Message>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub content: MessageContent,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Blocks(Vec<ContentBlock>),
+}
+
+impl Default for MessageContent {
+    fn default() -> Self {
+        MessageContent::Blocks(Vec::new())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+    },
+    Thinking {
+        #[serde(default)]
+        thinking: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        #[serde(default)]
+        input: Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        #[serde(default)]
+        content: Option<Value>,
+        #[serde(default)]
+        is_error: Option<bool>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Usage {
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+    #[serde(default)]
+    pub cache_creation_input_tokens: u64,
+    #[serde(default)]
+    pub cache_read_input_tokens: u64,
+}
+
+impl SessionEntry {
+    /// Best-effort entry for a JSON line the typed model does not fit
+    ///
+    /// Only the fields that have the expected type are read; the raw line is what gets written back.
+    fn untyped(raw: &Value) -> Self {
+        let string = |key: &str| raw.get(key).and_then(Value::as_str).map(str::to_string);
+        let flag = |key: &str| raw.get(key).and_then(Value::as_bool).unwrap_or(false);
+
+        Self {
+            kind: raw
+                .get("type")
+                .and_then(|kind| serde_json::from_value(kind.clone()).ok())
+                .unwrap_or(EntryKind::Unknown),
+            uuid: string("uuid"),
+            parent_uuid: string("parentUuid"),
+            session_id: string("sessionId"),
+            timestamp: string("timestamp"),
+            cwd: string("cwd"),
+            git_branch: string("gitBranch"),
+            is_sidechain: flag("isSidechain"),
+            is_meta: flag("isMeta"),
+            is_compact_summary: flag("isCompactSummary"),
+            subtype: string("subtype"),
+            summary: string("summary"),
+            leaf_uuid: string("leafUuid"),
+            message: raw
+                .get("message")
+                .and_then(|message| serde_json::from_value(message.clone()).ok()),
+            extra: Map::new(),
+        }
+    }
+
+    pub fn content_blocks(&self) -> &[ContentBlock] {
+        match self.message.as_ref().map(|m| &m.content) {
+            Some(MessageContent::Blocks(blocks)) => blocks,
+            _ => &[],
+        }
+    }
+
+    /// Plain text of the message, joining text blocks
+    pub fn text(&self) -> Option<String> {
+        match self.message.as_ref().map(|m| &m.content)? {
+            MessageContent::Text(text) => Some(text.clone()),
+            MessageContent::Blocks(blocks) => {
+                let text: Vec<&str> = blocks
+                    .iter()
+                    .filter_map(|b| match b {
+                        ContentBlock::Text { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                (!text.is_empty()).then(|| text.join("\n"))
+            }
+        }
+    }
+
+    /// Tool calls made in this entry as `(id, name, input)`
+    pub fn tool_uses(&self) -> impl Iterator<Item = (&str, &str, &Value)> {
+        self.content_blocks().iter().filter_map(|b| match b {
+            ContentBlock::ToolUse { id, name, input } => Some((id.as_str(), name.as_str(), input)),
+            _ => None,
+        })
+    }
+
+    /// Tool results carried by this entry as `(tool_use_id, content, is_error)`
+    pub fn tool_results(&self) -> impl Iterator<Item = (&str, Option<&Value>, bool)> {
+        self.content_blocks().iter().filter_map(|b| match b {
+            ContentBlock::ToolResult { tool_use_id, content, is_error } => {
+                Some((tool_use_id.as_str(), content.as_ref(), is_error.unwrap_or(false)))
+            }
+            _ => None,
+        })
+    }
+
+    /// A user-typed prompt, as opposed to a tool result or meta entry
+    pub fn is_turn_start(&self) -> bool {
+        if self.kind != EntryKind::User || self.is_meta || self.is_compact_summary {
+            return false;
+        }
+
+        match self.message.as_ref().map(|m| &m.content) {
+            Some(MessageContent::Text(_)) => true,
+            Some(MessageContent::Blocks(blocks)) => !blocks
+                .iter()
+                .any(|b| matches!(b, ContentBlock::ToolResult { .. })),
+            None => false,
+        }
+    }
+
+    /// A compaction marker written by Claude Code when the context was summarized
+    pub fn is_compact_boundary(&self) -> bool {
+        let is_boundary = self.kind == EntryKind::System
+            && self.subtype.as_deref() == Some("compact_boundary");
+        is_boundary || self.is_compact_summary
+    }
+
+    pub fn timestamp(&self) -> Option<DateTime<Utc>> {
+        self.timestamp
+            .as_deref()
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+            .map(|ts| ts.with_timezone(&Utc))
+    }
+}
+
+/// A parsed session line with its raw JSON kept for lossless rewriting
+#[derive(Debug, Clone)]
+pub struct TranscriptLine {
+    pub line_number: usize,
+    pub raw: Value,
+    pub entry: SessionEntry,
+}
+
+/// A line that is not JSON at all
+#[derive(Debug, Clone)]
+pub struct MalformedLine {
+    pub line_number: usize,
+    pub error: String,
+    /// The line as read, so rewrites can keep it in place
+    pub text: String,
+}
+
+/// A whole session file, parsed leniently
+#[derive(Debug, Clone, Default)]
+pub struct SessionTranscript {
+    pub lines: Vec<TranscriptLine>,
+    pub malformed: Vec<MalformedLine>,
+}
+
+impl SessionTranscript {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(Self::parse(&content))
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let mut transcript = Self::default();
+
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let line_number = index + 1;
+            match serde_json::from_str::<Value>(line) {
+                Ok(raw) => {
+                    // Newer or unexpected shapes are still valid session lines
+                    let entry = serde_json::from_value::<SessionEntry>(raw.clone()).unwrap_or_else(|e| {
+                        debug!("Keeping session line {} untyped: {}", line_number, e);
+                        SessionEntry::untyped(&raw)
+                    });
+                    transcript.lines.push(TranscriptLine {
+                        line_number,
+                        raw,
+                        entry,
+                    });
+                }
+                Err(e) => transcript.malformed.push(MalformedLine {
+                    line_number,
+                    error: e.to_string(),
+                    text: line.to_string(),
+                }),
+            }
+        }
+
+        transcript
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &SessionEntry> {
+        self.lines.iter().map(|l| &l.entry)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Serialize back to JSONL, with malformed lines passed through unchanged where they were
+    pub fn to_jsonl(&self) -> Result<String> {
+        let mut output = Vec::with_capacity(self.lines.len() + self.malformed.len());
+        for line in &self.lines {
+            output.push((line.line_number, serde_json::to_string(&line.raw)?));
+        }
+        for line in &self.malformed {
+            output.push((line.line_number, line.text.clone()));
+        }
+        output.sort_by_key(|(line_number, _)| *line_number);
+        Ok(output.into_iter().map(|(_, line)| line).collect::<Vec<_>>().join("\n"))
+    }
+}
@@ -0,0 +1,505 @@
+use clap::ValueEnum;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::model::{ContentBlock, EntryKind, MessageContent, SessionEntry, SessionTranscript};
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RenderFormat {
+    #[default]
+    #[value(name = "md")]
+    Markdown,
+    Html,
+}
+
+impl RenderFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RenderFormat::Markdown => "md",
+            RenderFormat::Html => "html",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    pub include_thinking: bool,
+    pub include_system: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            include_thinking: true,
+            include_system: true,
+        }
+    }
+}
+
+/// Format-independent pieces of a rendered session
+#[derive(Debug, Clone)]
+enum Block {
+    Turn { role: &'static str, timestamp: Option<String> },
+    Text(String),
+    Thinking(String),
+    ToolCall { name: String, summary: String, body: ToolBody },
+    ToolResult { name: String, content: String, is_error: bool },
+    Note(String),
+}
+
+#[derive(Debug, Clone)]
+enum ToolBody {
+    Diff(String),
+    Code { lang: &'static str, code: String },
+}
+
+pub struct SessionRenderer {
+    options: RenderOptions,
+}
+
+impl SessionRenderer {
+    pub fn new(options: RenderOptions) -> Self {
+        Self { options }
+    }
+
+    pub fn render(&self, transcript: &SessionTranscript, title: &str, format: RenderFormat) -> String {
+        let blocks = self.blocks(transcript);
+        match format {
+            RenderFormat::Markdown => Self::to_markdown(&blocks, title),
+            RenderFormat::Html => Self::to_html(&blocks, title),
+        }
+    }
+
+    fn blocks(&self, transcript: &SessionTranscript) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        let mut tool_names: HashMap<String, String> = HashMap::new();
+        let mut last_role: Option<&'static str> = None;
+
+        for entry in transcript.entries() {
+            let is_system = matches!(entry.kind, EntryKind::System | EntryKind::Summary)
+                || entry.is_meta
+                || entry.is_compact_summary;
+
+            if is_system {
+                if self.options.include_system {
+                    if let Some(note) = Self::system_note(entry) {
+                        blocks.push(Block::Note(note));
+                    }
+                    last_role = None;
+                }
+                continue;
+            }
+
+            let role = match entry.kind {
+                EntryKind::User if entry.is_turn_start() => "User",
+                EntryKind::User => "Tool results",
+                EntryKind::Assistant => "Assistant",
+                _ => continue,
+            };
+
+            // Consecutive assistant entries belong to one turn
+            if last_role != Some(role) || role == "User" {
+                blocks.push(Block::Turn {
+                    role,
+                    timestamp: entry.timestamp.clone(),
+                });
+                last_role = Some(role);
+            }
+
+            let content = match entry.message.as_ref().map(|m| &m.content) {
+                Some(content) => content,
+                None => continue,
+            };
+
+            match content {
+                MessageContent::Text(text) => blocks.push(Block::Text(text.clone())),
+                MessageContent::Blocks(content_blocks) => {
+                    for block in content_blocks {
+                        match block {
+                            ContentBlock::Text { text } => blocks.push(Block::Text(text.clone())),
+                            ContentBlock::Thinking { thinking } => {
+                                if self.options.include_thinking && !thinking.is_empty() {
+                                    blocks.push(Block::Thinking(thinking.clone()));
+                                }
+                            }
+                            ContentBlock::ToolUse { id, name, input } => {
+                                tool_names.insert(id.clone(), name.clone());
+                                blocks.push(Self::tool_call(name, input));
+                            }
+                            ContentBlock::ToolResult { tool_use_id, content, is_error } => {
+                                blocks.push(Block::ToolResult {
+                                    name: tool_names
+                                        .get(tool_use_id)
+                                        .cloned()
+                                        .unwrap_or_else(|| "tool".to_string()),
+                                    content: content.as_ref().map(result_text).unwrap_or_default(),
+                                    is_error: is_error.unwrap_or(false),
+                                });
+                            }
+                            ContentBlock::Other => {}
+                        }
+                    }
+                }
+            }
+        }
+
+        blocks
+    }
+
+    fn system_note(entry: &SessionEntry) -> Option<String> {
+        if let Some(summary) = &entry.summary {
+            return Some(format!("Summary: {}", summary));
+        }
+        if entry.is_compact_boundary() {
+            return Some("Conversation compacted".to_string());
+        }
+        entry
+            .text()
+            .or_else(|| entry.extra.get("content").and_then(Value::as_str).map(str::to_string))
+    }
+
+    fn tool_call(name: &str, input: &Value) -> Block {
+        let path = input
+            .get("file_path")
+            .or_else(|| input.get("notebook_path"))
+            .and_then(Value::as_str);
+
+        let (summary, body) = match name {
+            "Edit" => {
+                let diff = edit_diff(
+                    path.unwrap_or_default(),
+                    &[(str_field(input, "old_string"), str_field(input, "new_string"))],
+                );
+                (path.unwrap_or_default().to_string(), ToolBody::Diff(diff))
+            }
+            "MultiEdit" => {
+                let edits: Vec<(&str, &str)> = input
+                    .get("edits")
+                    .and_then(Value::as_array)
+                    .map(|edits| {
+                        edits
+                            .iter()
+                            .map(|e| (str_field(e, "old_string"), str_field(e, "new_string")))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let diff = edit_diff(path.unwrap_or_default(), &edits);
+                (path.unwrap_or_default().to_string(), ToolBody::Diff(diff))
+            }
+            "Write" => (
+                path.unwrap_or_default().to_string(),
+                ToolBody::Code {
+                    lang: "",
+                    code: str_field(input, "content").to_string(),
+                },
+            ),
+            "Bash" => (
+                input
+                    .get("description")
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                ToolBody::Code {
+                    lang: "bash",
+                    code: str_field(input, "command").to_string(),
+                },
+            ),
+            _ => (
+                path.unwrap_or_default().to_string(),
+                ToolBody::Code {
+                    lang: "json",
+                    code: serde_json::to_string_pretty(input).unwrap_or_default(),
+                },
+            ),
+        };
+
+        Block::ToolCall {
+            name: name.to_string(),
+            summary,
+            body,
+        }
+    }
+
+    fn to_markdown(blocks: &[Block], title: &str) -> String {
+        let mut md = String::new();
+        let _ = writeln!(md, "# {}", title);
+
+        for block in blocks {
+            match block {
+                Block::Turn { role, timestamp } => {
+                    let _ = writeln!(md);
+                    match timestamp {
+                        Some(ts) => {
+                            let _ = writeln!(md, "## {} · {}", role, ts);
+                        }
+                        None => {
+                            let _ = writeln!(md, "## {}", role);
+                        }
+                    }
+                    let _ = writeln!(md);
+                }
+                Block::Text(text) => {
+                    let _ = writeln!(md, "{}", text);
+                    let _ = writeln!(md);
+                }
+                Block::Thinking(thinking) => {
+                    let _ = writeln!(md, "<details><summary>Thinking</summary>");
+                    let _ = writeln!(md);
+                    for line in thinking.lines() {
+                        let _ = writeln!(md, "> {}", line);
+                    }
+                    let _ = writeln!(md);
+                    let _ = writeln!(md, "</details>");
+                    let _ = writeln!(md);
+                }
+                Block::ToolCall { name, summary, body } => {
+                    let _ = writeln!(md, "<details><summary>🔧 {} {}</summary>", name, summary);
+                    let _ = writeln!(md);
+                    let (lang, code) = match body {
+                        ToolBody::Diff(diff) => ("diff", diff.as_str()),
+                        ToolBody::Code { lang, code } => (*lang, code.as_str()),
+                    };
+                    let fence = fence_for(code);
+                    let _ = writeln!(md, "{}{}", fence, lang);
+                    let _ = writeln!(md, "{}", code);
+                    let _ = writeln!(md, "{}", fence);
+                    let _ = writeln!(md);
+                    let _ = writeln!(md, "</details>");
+                    let _ = writeln!(md);
+                }
+                Block::ToolResult { name, content, is_error } => {
+                    let label = if *is_error { "❌ Error" } else { "Result" };
+                    let _ = writeln!(md, "<details><summary>{}: {}</summary>", label, name);
+                    let _ = writeln!(md);
+                    let fence = fence_for(content);
+                    let _ = writeln!(md, "{}", fence);
+                    let _ = writeln!(md, "{}", content);
+                    let _ = writeln!(md, "{}", fence);
+                    let _ = writeln!(md);
+                    let _ = writeln!(md, "</details>");
+                    let _ = writeln!(md);
+                }
+                Block::Note(note) => {
+                    let _ = writeln!(md);
+                    let _ = writeln!(md, "_{}_", note.replace('\n', " "));
+                    let _ = writeln!(md);
+                }
+            }
+        }
+
+        md
+    }
+
+    fn to_html(blocks: &[Block], title: &str) -> String {
+        let mut html = String::new();
+        let _ = writeln!(html, "<!DOCTYPE html>");
+        let _ = writeln!(html, "<html lang=\"en\"><head><meta charset=\"utf-8\">");
+        let _ = writeln!(html, "<title>{}</title>", escape_html(title));
+        let _ = writeln!(html, "<style>{}</style>", HTML_STYLE);
+        let _ = writeln!(html, "</head><body><main>");
+        let _ = writeln!(html, "<h1>{}</h1>", escape_html(title));
+
+        let mut open_turn = false;
+        for block in blocks {
+            match block {
+                Block::Turn { role, timestamp } => {
+                    if open_turn {
+                        let _ = writeln!(html, "</section>");
+                    }
+                    let class = role.to_lowercase().replace(' ', "-");
+                    let _ = writeln!(html, "<section class=\"turn {}\">", class);
+                    let _ = write!(html, "<h2>{}", role);
+                    if let Some(ts) = timestamp {
+                        let _ = write!(html, " <time>{}</time>", escape_html(ts));
+                    }
+                    let _ = writeln!(html, "</h2>");
+                    open_turn = true;
+                }
+                Block::Text(text) => {
+                    let _ = writeln!(html, "<div class=\"text\">{}</div>", escape_html(text));
+                }
+                Block::Thinking(thinking) => {
+                    let _ = writeln!(
+                        html,
+                        "<details class=\"thinking\"><summary>Thinking</summary><div class=\"text\">{}</div></details>",
+                        escape_html(thinking)
+                    );
+                }
+                Block::ToolCall { name, summary, body } => {
+                    let _ = write!(
+                        html,
+                        "<details class=\"tool\"><summary>🔧 {} <code>{}</code></summary>",
+                        escape_html(name),
+                        escape_html(summary)
+                    );
+                    match body {
+                        ToolBody::Diff(diff) => {
+                            let _ = write!(html, "<pre class=\"diff\">");
+                            for line in diff.lines() {
+                                let class = match line.chars().next() {
+                                    Some('+') => "add",
+                                    Some('-') => "del",
+                                    _ => "ctx",
+                                };
+                                let _ = writeln!(html, "<span class=\"{}\">{}</span>", class, escape_html(line));
+                            }
+                            let _ = write!(html, "</pre>");
+                        }
+                        ToolBody::Code { code, .. } => {
+                            let _ = write!(html, "<pre>{}</pre>", escape_html(code));
+                        }
+                    }
+                    let _ = writeln!(html, "</details>");
+                }
+                Block::ToolResult { name, content, is_error } => {
+                    let (class, label) = if *is_error {
+                        ("result error", "❌ Error")
+                    } else {
+                        ("result", "Result")
+                    };
+                    let _ = writeln!(
+                        html,
+                        "<details class=\"{}\"><summary>{}: {}</summary><pre>{}</pre></details>",
+                        class,
+                        label,
+                        escape_html(name),
+                        escape_html(content)
+                    );
+                }
+                Block::Note(note) => {
+                    let _ = writeln!(html, "<p class=\"note\">{}</p>", escape_html(note));
+                }
+            }
+        }
+
+        if open_turn {
+            let _ = writeln!(html, "</section>");
+        }
+        let _ = writeln!(html, "</main></body></html>");
+        html
+    }
+}
+
+const HTML_STYLE: &str = "\
+body{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;background:#f6f7f9;color:#1f2328;margin:0}\
+main{max-width:960px;margin:0 auto;padding:24px}\
+h1{font-size:1.4em}\
+.turn{background:#fff;border:1px solid #d0d7de;border-radius:8px;padding:12px 16px;margin:12px 0}\
+.turn.user{border-left:4px solid #0969da}\
+.turn.assistant{border-left:4px solid #8250df}\
+.turn.tool-results{border-left:4px solid #6e7781}\
+h2{font-size:1em;margin:0 0 8px}\
+time{color:#6e7781;font-weight:normal;font-size:.85em}\
+.text{white-space:pre-wrap}\
+details{margin:6px 0}\
+summary{cursor:pointer;color:#57606a}\
+pre{background:#f6f8fa;border-radius:6px;padding:8px;overflow-x:auto;white-space:pre-wrap}\
+.diff .add{color:#116329;background:#dafbe1;display:block}\
+.diff .del{color:#82071e;background:#ffebe9;display:block}\
+.diff .ctx{display:block;color:#57606a}\
+.result.error summary{color:#cf222e}\
+.note{color:#6e7781;font-style:italic}";
+
+fn str_field<'a>(value: &'a Value, field: &str) -> &'a str {
+    value.get(field).and_then(Value::as_str).unwrap_or_default()
+}
+
+fn edit_diff(path: &str, edits: &[(&str, &str)]) -> String {
+    let mut diff = String::new();
+    let _ = writeln!(diff, "--- a/{}", path.trim_start_matches('/'));
+    let _ = writeln!(diff, "+++ b/{}", path.trim_start_matches('/'));
+    for (old, new) in edits {
+        let _ = writeln!(diff, "@@");
+        for line in old.lines() {
+            let _ = writeln!(diff, "-{}", line);
+        }
+        for line in new.lines() {
+            let _ = writeln!(diff, "+{}", line);
+        }
+    }
+    diff.trim_end().to_string()
+}
+
+pub(crate) fn result_text(content: &Value) -> String {
+    match content {
+        Value::String(s) => s.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .map(|b| match b.get("text").and_then(Value::as_str) {
+                Some(text) => text.to_string(),
+                None => format!("[{}]", b.get("type").and_then(Value::as_str).unwrap_or("content")),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => other.to_string(),
+    }
+}
+
+/// A code fence longer than any backtick run inside the code
+fn fence_for(code: &str) -> String {
+    let mut longest = 0;
+    let mut current = 0;
+    for c in code.chars() {
+        if c == '`' {
+            current += 1;
+            longest = longest.max(current);
+        } else {
+            current = 0;
+        }
+    }
+    "`".repeat(longest.max(2) + 1)
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION: &str = r#"{"type":"summary","summary":"Login work","leafUuid":"b"}
+{"type":"user","uuid":"a","timestamp":"2025-01-01T10:00:00Z","message":{"role":"user","content":"rename <foo>"}}
+{"type":"assistant","uuid":"b","parentUuid":"a","message":{"role":"assistant","content":[{"type":"thinking","thinking":"simple rename"},{"type":"tool_use","id":"t1","name":"Edit","input":{"file_path":"src/lib.rs","old_string":"fn foo()","new_string":"fn bar()"}}]}}
+{"type":"user","uuid":"c","parentUuid":"b","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"updated"}]}}"#;
+
+    #[test]
+    fn test_render_markdown_with_diff() {
+        let transcript = SessionTranscript::parse(SESSION);
+        let md = SessionRenderer::new(RenderOptions::default())
+            .render(&transcript, "Session", RenderFormat::Markdown);
+
+        assert!(md.contains("## User · 2025-01-01T10:00:00Z"));
+        assert!(md.contains("-fn foo()\n+fn bar()"));
+        assert!(md.contains("Result: Edit"));
+        assert!(md.contains("Thinking"));
+        assert!(md.contains("_Summary: Login work_"));
+    }
+
+    #[test]
+    fn test_render_html_filters_and_escapes() {
+        let transcript = SessionTranscript::parse(SESSION);
+        let options = RenderOptions {
+            include_thinking: false,
+            include_system: false,
+        };
+        let html = SessionRenderer::new(options).render(&transcript, "Session", RenderFormat::Html);
+
+        assert!(html.contains("rename &lt;foo&gt;"));
+        assert!(!html.contains("simple rename"));
+        assert!(!html.contains("Login work"));
+        assert!(!html.contains("<link") && !html.contains("<script"));
+    }
+}
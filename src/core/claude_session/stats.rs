@@ -0,0 +1,205 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use super::model::{EntryKind, SessionTranscript};
+
+/// Tools whose input names a file on disk
+pub const FILE_TOOLS: &[&str] = &["Read", "Edit", "MultiEdit", "Write", "NotebookEdit"];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+}
+
+/// Summary statistics of a session, carried in the beam metadata
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionStats {
+    pub entries: usize,
+    pub turns: usize,
+    pub tool_calls: BTreeMap<String, usize>,
+    pub files_touched: Vec<String>,
+    pub models: Vec<String>,
+    pub token_usage: TokenUsage,
+}
+
+impl SessionStats {
+    pub fn from_transcript(transcript: &SessionTranscript) -> Self {
+        let mut stats = Self {
+            entries: transcript.len(),
+            ..Default::default()
+        };
+        let mut files = BTreeSet::new();
+        let mut models = BTreeSet::new();
+
+        for entry in transcript.entries() {
+            if entry.is_turn_start() {
+                stats.turns += 1;
+            }
+
+            for (_, name, input) in entry.tool_uses() {
+                *stats.tool_calls.entry(name.to_string()).or_default() += 1;
+                if let Some(path) = tool_file_path(name, input) {
+                    files.insert(path.to_string());
+                }
+            }
+
+            if entry.kind == EntryKind::Assistant && let Some(message) = &entry.message {
+                if let Some(model) = &message.model {
+                    models.insert(model.clone());
+                }
+                if let Some(usage) = &message.usage {
+                    stats.token_usage.input_tokens += usage.input_tokens;
+                    stats.token_usage.output_tokens += usage.output_tokens;
+                    stats.token_usage.cache_creation_input_tokens += usage.cache_creation_input_tokens;
+                    stats.token_usage.cache_read_input_tokens += usage.cache_read_input_tokens;
+                }
+            }
+        }
+
+        stats.files_touched = files.into_iter().collect();
+        stats.models = models.into_iter().collect();
+        stats
+    }
+
+    pub fn total_tool_calls(&self) -> usize {
+        self.tool_calls.values().sum()
+    }
+
+    /// Print a short human-readable summary, indented under a heading
+    pub fn print_summary(&self) {
+        println!("   Turns: {}, tool calls: {}", self.turns, self.total_tool_calls());
+        if !self.tool_calls.is_empty() {
+            let mut by_count: Vec<_> = self.tool_calls.iter().collect();
+            by_count.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+            let top: Vec<String> = by_count
+                .iter()
+                .take(5)
+                .map(|(name, count)| format!("{} {}", name, count))
+                .collect();
+            println!("   Tools: {}", top.join(", "));
+        }
+        if !self.files_touched.is_empty() {
+            println!("   Files touched: {}", self.files_touched.len());
+        }
+        if !self.models.is_empty() {
+            println!("   Models: {}", self.models.join(", "));
+        }
+        let usage = &self.token_usage;
+        if usage.input_tokens + usage.output_tokens > 0 {
+            println!(
+                "   Tokens: {} in, {} out, {} cache read",
+                usage.input_tokens, usage.output_tokens, usage.cache_read_input_tokens
+            );
+        }
+    }
+}
+
+/// The file a file-oriented tool call operates on
+pub fn tool_file_path<'a>(name: &str, input: &'a Value) -> Option<&'a str> {
+    if !FILE_TOOLS.contains(&name) {
+        return None;
+    }
+    input
+        .get("file_path")
+        .or_else(|| input.get("notebook_path"))
+        .and_then(Value::as_str)
+}
+
+/// Structural problems found in a session file
+#[derive(Debug, Clone, Default)]
+pub struct SessionValidation {
+    /// Line numbers that are not valid session entries
+    pub malformed_lines: Vec<usize>,
+    /// Entries whose `parentUuid` does not refer to any entry in the file
+    pub broken_parents: Vec<String>,
+    /// Entry uuids that appear more than once
+    pub duplicate_uuids: Vec<String>,
+}
+
+impl SessionValidation {
+    pub fn check(transcript: &SessionTranscript) -> Self {
+        let mut validation = Self {
+            malformed_lines: transcript.malformed.iter().map(|m| m.line_number).collect(),
+            ..Default::default()
+        };
+
+        let mut seen = HashSet::new();
+        for entry in transcript.entries() {
+            if let Some(uuid) = &entry.uuid && !seen.insert(uuid.as_str()) {
+                validation.duplicate_uuids.push(uuid.clone());
+            }
+        }
+
+        for entry in transcript.entries() {
+            if let (Some(uuid), Some(parent)) = (&entry.uuid, &entry.parent_uuid) && !seen.contains(parent.as_str()) {
+                validation.broken_parents.push(uuid.clone());
+            }
+        }
+
+        validation
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.malformed_lines.is_empty()
+            && self.broken_parents.is_empty()
+            && self.duplicate_uuids.is_empty()
+    }
+
+    /// Human-readable descriptions of each problem class
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !self.malformed_lines.is_empty() {
+            problems.push(format!(
+                "{} malformed lines (first at line {})",
+                self.malformed_lines.len(),
+                self.malformed_lines[0]
+            ));
+        }
+        if !self.broken_parents.is_empty() {
+            problems.push(format!(
+                "{} entries with a missing parent (first: {})",
+                self.broken_parents.len(),
+                self.broken_parents[0]
+            ));
+        }
+        if !self.duplicate_uuids.is_empty() {
+            problems.push(format!("{} duplicate entry uuids", self.duplicate_uuids.len()));
+        }
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION: &str = r#"{"type":"user","uuid":"a","parentUuid":null,"message":{"role":"user","content":"fix the bug"}}
+{"type":"assistant","uuid":"b","parentUuid":"a","message":{"role":"assistant","model":"claude-sonnet-4","content":[{"type":"tool_use","id":"t1","name":"Edit","input":{"file_path":"src/lib.rs"}}],"usage":{"input_tokens":10,"output_tokens":5},"stop_reason":null},"newField":1}
+{"type":"user","uuid":"c","parentUuid":"b","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"ok"}]}}
+not json
+{"type":"assistant","uuid":"d","parentUuid":"missing","message":{"role":"assistant","content":[{"type":"text","text":"done"}]}}
+{"type":"user","uuid":"e","parentUuid":"d","message":{"role":"user","content":[{"type":"text"}]}}
+{"uuid":"f","parentUuid":"e","newShape":true}"#;
+
+    #[test]
+    fn test_stats_and_validation() {
+        let transcript = SessionTranscript::parse(SESSION);
+        assert_eq!(transcript.len(), 6);
+
+        let stats = SessionStats::from_transcript(&transcript);
+        assert_eq!(stats.turns, 1);
+        assert_eq!(stats.tool_calls.get("Edit"), Some(&1));
+        assert_eq!(stats.files_touched, vec!["src/lib.rs".to_string()]);
+        assert_eq!(stats.models, vec!["claude-sonnet-4".to_string()]);
+        assert_eq!(stats.token_usage.output_tokens, 5);
+
+        let validation = SessionValidation::check(&transcript);
+        assert_eq!(validation.malformed_lines, vec![4]);
+        assert_eq!(validation.broken_parents, vec!["d".to_string()]);
+        assert!(!validation.is_valid());
+    }
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use super::model::SessionTranscript;
+use super::stats::tool_file_path;
+
+/// Tools that change the file they name
+pub const MODIFYING_TOOLS: &[&str] = &["Edit", "MultiEdit", "Write", "NotebookEdit"];
+
+/// A workspace file the agent changed during the session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TouchedFile {
+    /// Path relative to the workspace, with `/` separators
+    pub path: String,
+    /// Uuid of the session entry that last modified the file
+    pub last_entry_uuid: String,
+}
+
+impl TouchedFile {
+    /// Files under `workspace` changed by the transcript's tool calls, sorted by path
+    ///
+    /// Relative tool paths are resolved against the entry's `cwd`; files outside the workspace
+    /// are left out.
+    pub fn from_transcript(transcript: &SessionTranscript, workspace: &Path) -> Vec<Self> {
+        let mut touched: BTreeMap<String, String> = BTreeMap::new();
+
+        for entry in transcript.entries() {
+            let Some(uuid) = &entry.uuid else {
+                continue;
+            };
+
+            for (_, name, input) in entry.tool_uses() {
+                if !MODIFYING_TOOLS.contains(&name) {
+                    continue;
+                }
+                let Some(path) = tool_file_path(name, input) else {
+                    continue;
+                };
+
+                let path = Path::new(path);
+                let absolute = match (&entry.cwd, path.is_absolute()) {
+                    (Some(cwd), false) => Path::new(cwd).join(path),
+                    _ => path.to_path_buf(),
+                };
+                if let Some(relative) = relative_to_workspace(&absolute, workspace) {
+                    touched.insert(relative, uuid.clone());
+                }
+            }
+        }
+
+        touched
+            .into_iter()
+            .map(|(path, last_entry_uuid)| Self { path, last_entry_uuid })
+            .collect()
+    }
+}
+
+fn relative_to_workspace(path: &Path, workspace: &Path) -> Option<String> {
+    // Normalize `.` and `..` without touching the filesystem; the file may be gone
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+
+    let relative = normalized.strip_prefix(workspace).ok()?;
+    let relative = relative.to_str()?.replace('\\', "/");
+    (!relative.is_empty()).then_some(relative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION: &str = r#"{"type":"assistant","uuid":"a","cwd":"/work/app","message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Write","input":{"file_path":"/work/app/src/lib.rs"}},{"type":"tool_use","id":"t2","name":"Read","input":{"file_path":"/work/app/README.md"}}]}}
+{"type":"assistant","uuid":"b","cwd":"/work/app/src","message":{"role":"assistant","content":[{"type":"tool_use","id":"t3","name":"Edit","input":{"file_path":"lib.rs"}},{"type":"tool_use","id":"t4","name":"Edit","input":{"file_path":"/etc/hosts"}}]}}
+{"type":"assistant","uuid":"c","cwd":"/work/app","message":{"role":"assistant","content":[{"type":"tool_use","id":"t5","name":"NotebookEdit","input":{"notebook_path":"/work/app/notes/../analysis.ipynb"}}]}}"#;
+
+    #[test]
+    fn test_touched_files_keep_last_modifying_entry() {
+        let transcript = SessionTranscript::parse(SESSION);
+        let touched = TouchedFile::from_transcript(&transcript, Path::new("/work/app"));
+
+        assert_eq!(
+            touched,
+            vec![
+                TouchedFile { path: "analysis.ipynb".to_string(), last_entry_uuid: "c".to_string() },
+                TouchedFile { path: "src/lib.rs".to_string(), last_entry_uuid: "b".to_string() },
+            ]
+        );
+    }
+}
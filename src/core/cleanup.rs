@@ -35,10 +35,8 @@ impl Drop for TempDirGuard {
             warn!("Cleanup may be incomplete due to panic");
         }
         
-        if let Err(e) = std::fs::remove_dir_all(&self.path) {
-            if self.path.exists() {
-                warn!("Failed to cleanup temporary directory {}: {}", self.path.display(), e);
-            }
+        if let Err(e) = std::fs::remove_dir_all(&self.path) && self.path.exists() {
+            warn!("Failed to cleanup temporary directory {}: {}", self.path.display(), e);
         }
     }
 }
\ No newline at end of file
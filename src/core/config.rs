@@ -1,3 +1,4 @@
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 use url::Url;
 use iroh_blobs::Hash;
@@ -7,28 +8,36 @@ pub const WARN_THRESHOLD: u64 = 1_000_000_000;
 pub const STREAM_BUFFER_SIZE: usize = 8192;
 pub const TEMP_DIR_PREFIX: &str = ".agentbeam-";
 
-pub const DEFAULT_EXCLUDES: &[&str] = &[
-    ".git/objects/",
-    "node_modules/",
-    "target/",
-    "dist/",
-    "build/",
-    "*.log",
-    ".env*",
-    ".agentbeam-*",
-];
-
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum ConnectionMode {
     Direct,
+    #[default]
     DefaultRelay,
     CustomRelay(Url),
 }
 
-impl Default for ConnectionMode {
-    fn default() -> Self {
-        ConnectionMode::DefaultRelay
-    }
+/// What to do when the secret scanner finds credentials in workspace files
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SecretPolicy {
+    /// Refuse to beam until the findings are resolved
+    #[default]
+    Block,
+    /// List the findings and continue
+    Warn,
+    /// Drop the affected files and add them to .beamignore
+    Exclude,
+}
+
+/// How Claude Code config in the workspace is shared
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClaudeConfigPolicy {
+    /// Leave out settings.local.json, strip permissions.allow and redact MCP env values
+    #[default]
+    Strip,
+    /// Leave out every Claude settings and MCP config file
+    Exclude,
+    /// Share config files unchanged
+    Keep,
 }
 
 #[derive(Debug, Clone)]
@@ -38,6 +47,33 @@ pub struct BeamConfig {
     pub warn_threshold: u64,
     pub force: bool,
     pub test_mode: bool,
+    pub secret_policy: SecretPolicy,
+    pub claude_config_policy: ClaudeConfigPolicy,
+    pub session_cutoff: Option<crate::core::claude_session::SessionCutoff>,
+    pub session_only: bool,
+    /// Beam only the files the agent modified, plus the session
+    pub agent_touched_only: bool,
+    /// Bundle every ref instead of only the checked-out branch
+    pub git_all_refs: bool,
+    /// Ship HEAD, unpushed commits and a patch instead of the workspace files
+    pub thin: bool,
+    /// Ship local LFS objects for files whose working tree holds only the pointer
+    pub include_lfs: bool,
+    pub attach_handoff: bool,
+    /// Agents whose sessions are beamed; empty means every detected agent
+    pub agents: Vec<crate::core::agents::AgentKind>,
+    /// How long the ticket stays valid; `None` never expires
+    pub ticket_ttl: Option<std::time::Duration>,
+    /// Name shown to receivers in the ticket preview
+    pub sender_name: Option<String>,
+    /// Encrypt blobs with a key derived from a passphrase before import
+    pub encrypt: bool,
+}
+
+impl BeamConfig {
+    pub fn includes_agent(&self, agent: crate::core::agents::AgentKind) -> bool {
+        self.agents.is_empty() || self.agents.contains(&agent)
+    }
 }
 
 impl Default for BeamConfig {
@@ -48,6 +84,19 @@ impl Default for BeamConfig {
             warn_threshold: WARN_THRESHOLD,
             force: false,
             test_mode: false,
+            secret_policy: SecretPolicy::default(),
+            claude_config_policy: ClaudeConfigPolicy::default(),
+            session_cutoff: None,
+            session_only: false,
+            agent_touched_only: false,
+            git_all_refs: false,
+            thin: false,
+            include_lfs: false,
+            attach_handoff: false,
+            agents: Vec::new(),
+            ticket_ttl: Some(crate::core::ticket::DEFAULT_TICKET_TTL),
+            sender_name: None,
+            encrypt: false,
         }
     }
 }
@@ -60,6 +109,9 @@ pub struct BeamContent {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BeamMetadata {
+    /// Layout version; absent in metadata written before versioning
+    #[serde(default)]
+    pub schema_version: crate::core::schema::SchemaVersion,
     pub session_id: String,
     pub workspace_name: String,
     pub created_at: u64,
@@ -68,4 +120,45 @@ pub struct BeamMetadata {
     pub file_count: usize,
     pub claude_session: Option<crate::core::claude_session::ClaudeSessionInfo>,
     pub git_context: Option<crate::core::claude_session::GitContext>,
+    #[serde(default)]
+    pub session_stats: Option<crate::core::claude_session::SessionStats>,
+    /// The beam carries no workspace files, only the session and git context
+    #[serde(default)]
+    pub session_only: bool,
+    /// Sessions of every agent included in the beam
+    #[serde(default)]
+    pub agent_sessions: Vec<crate::core::agents::AgentSessionInfo>,
+    /// Workspace files the Claude session modified
+    #[serde(default)]
+    pub agent_touched_files: Vec<crate::core::claude_session::TouchedFile>,
+    /// The beam carries only the agent-touched files, not the whole workspace
+    #[serde(default)]
+    pub agent_touched_only: bool,
+    /// Git history shipped as a bundle in place of the `.git` directory
+    #[serde(default)]
+    pub git_bundle: Option<crate::core::git_transfer::GitBundleInfo>,
+    /// The receiver reconstructs the workspace from its own clone
+    #[serde(default)]
+    pub thin_beam: Option<crate::core::git_transfer::ThinBeamInfo>,
+    /// Fields written by a newer minor schema version, kept so they survive a round trip
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl BeamMetadata {
+    /// Parse metadata of any compatible schema version; see [`crate::core::schema`]
+    pub fn from_json(content: &str) -> anyhow::Result<Self> {
+        crate::core::schema::parse_metadata(content)
+    }
+
+    pub fn load(path: &std::path::Path) -> anyhow::Result<Self> {
+        use anyhow::Context;
+        let content = std::fs::read_to_string(path).context("Beam metadata not found in package")?;
+        Self::from_json(&content)
+    }
+
+    /// Fields this version does not know, from a sender on a newer minor schema version
+    pub fn unknown_fields(&self) -> Vec<&str> {
+        self.extra.keys().map(String::as_str).collect()
+    }
 }
\ No newline at end of file
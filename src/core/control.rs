@@ -0,0 +1,474 @@
+use anyhow::{Context, Result};
+use iroh::endpoint::{ConnectError, Connection, ConnectionError, RecvStream, SendStream, TransportErrorCode};
+use iroh::protocol::{AcceptError, ProtocolHandler};
+use iroh::{Endpoint, NodeId};
+use iroh_blobs::{ticket::BlobTicket, Hash};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::debug;
+
+use crate::core::receipt::SignedReceipt;
+use crate::core::ticket::unix_now;
+
+/// ALPN of the control protocol, served next to `iroh_blobs::ALPN`
+pub const CONTROL_ALPN: &[u8] = b"agentbeam/1";
+
+/// Bumped when the messages change incompatibly; the ALPN carries the same number
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Features this build supports, exchanged in the handshake
+pub const CAPABILITIES: &[&str] = &["manifest", "receipt", "encryption"];
+
+/// How long the sender waits for the receiver's handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long an admitted receiver may take to download, verify and restore before the beam is freed
+const COMPLETION_TIMEOUT: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// TLS `no_application_protocol` alert, sent by peers that do not serve the ALPN
+const NO_APPLICATION_PROTOCOL: u8 = 120;
+
+/// Upper bound for one framed message
+const MAX_MESSAGE_SIZE: usize = 64 * 1024;
+
+/// Why a beam was declined or failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorReason {
+    Expired,
+    NotAuthorized,
+    UnknownCollection,
+    UnsupportedVersion,
+    VerificationFailed,
+    TransferFailed,
+    /// Restoring the workspace or session after the download failed
+    RestoreFailed,
+    /// A message arrived out of order or could not be parsed
+    Protocol,
+}
+
+impl fmt::Display for ErrorReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            ErrorReason::Expired => "the ticket has expired",
+            ErrorReason::NotAuthorized => "another receiver is already receiving this beam",
+            ErrorReason::UnknownCollection => "the sender is not sharing this collection",
+            ErrorReason::UnsupportedVersion => "the two AgentBeam versions cannot talk to each other",
+            ErrorReason::VerificationFailed => "the received files did not match the signed manifest",
+            ErrorReason::TransferFailed => "the download or export failed",
+            ErrorReason::RestoreFailed => "restoring the workspace or session failed",
+            ErrorReason::Protocol => "unexpected control message",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+/// Messages on the control stream, framed as a big-endian u32 length and JSON
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ControlMessage {
+    /// Receiver to sender, first message on the stream
+    Hello {
+        version: u32,
+        agentbeam_version: String,
+        capabilities: Vec<String>,
+        collection: Hash,
+        display_name: Option<String>,
+    },
+    Accept {
+        version: u32,
+        agentbeam_version: String,
+        capabilities: Vec<String>,
+    },
+    Decline { reason: ErrorReason },
+    /// Receiver to sender once the export matched the manifest
+    Complete {
+        root_hash: Hash,
+        file_count: u64,
+        #[serde(default)]
+        receipt: Option<SignedReceipt>,
+    },
+    Failed { reason: ErrorReason, message: String },
+    /// Sender to receiver, confirming `Complete` or `Failed`
+    Ack,
+}
+
+/// What the control protocol tells the sender's [`crate::core::provider_monitor::ProviderMonitor`]
+#[derive(Debug, Clone)]
+pub enum ControlEvent {
+    Accepted {
+        node_id: NodeId,
+        display_name: Option<String>,
+    },
+    Declined {
+        node_id: NodeId,
+        reason: ErrorReason,
+    },
+    Completed {
+        node_id: NodeId,
+        root_hash: Hash,
+        file_count: u64,
+        /// Verified delivery receipt, signed by the receiver
+        receipt: Option<SignedReceipt>,
+    },
+    /// The receiver reported a failure or went away before completing
+    Failed {
+        node_id: NodeId,
+        message: String,
+    },
+}
+
+/// Sender side of `agentbeam/1`: admits one receiver at a time for one collection
+#[derive(Debug, Clone)]
+pub struct ControlProtocol {
+    sender: NodeId,
+    collection: Hash,
+    expires_at: Option<u64>,
+    events: mpsc::Sender<ControlEvent>,
+    /// Receiver currently admitted; released again if it fails
+    claimed: Arc<Mutex<Option<NodeId>>>,
+    /// Capabilities a receiver must announce to be admitted
+    required: Vec<&'static str>,
+}
+
+impl ControlProtocol {
+    pub fn new(sender: NodeId, collection: Hash, expires_at: Option<u64>, events: mpsc::Sender<ControlEvent>) -> Self {
+        Self {
+            sender,
+            collection,
+            expires_at,
+            events,
+            claimed: Arc::new(Mutex::new(None)),
+            required: Vec::new(),
+        }
+    }
+
+    /// Decline receivers that do not announce `capability`, e.g. `encryption` for an encrypted beam
+    pub fn require(mut self, capability: &'static str) -> Self {
+        self.required.push(capability);
+        self
+    }
+
+    /// Decide on a handshake, claiming the beam for `node_id` if it is accepted
+    fn admit(&self, node_id: NodeId, version: u32, collection: Hash) -> Option<ErrorReason> {
+        if version != PROTOCOL_VERSION {
+            return Some(ErrorReason::UnsupportedVersion);
+        }
+        if collection != self.collection {
+            return Some(ErrorReason::UnknownCollection);
+        }
+        if self.expires_at.is_some_and(|expires_at| unix_now() >= expires_at) {
+            return Some(ErrorReason::Expired);
+        }
+
+        let mut claimed = self.claimed.lock().unwrap();
+        match *claimed {
+            Some(holder) if holder != node_id => Some(ErrorReason::NotAuthorized),
+            _ => {
+                *claimed = Some(node_id);
+                None
+            }
+        }
+    }
+
+    /// Check that a receipt is signed by `node_id` and describes this beam
+    fn check_receipt(&self, node_id: NodeId, file_count: u64, signed: &SignedReceipt) -> Result<()> {
+        let (receiver, receipt) = signed.verify()?;
+        if receiver != node_id {
+            anyhow::bail!("receipt is signed by {} but came from {}", receiver, node_id);
+        }
+        if receipt.collection != self.collection.to_string() || receipt.sender != self.sender.to_string() {
+            anyhow::bail!("receipt is for another beam ({} from {})", receipt.collection, receipt.sender);
+        }
+        if receipt.file_count != file_count {
+            anyhow::bail!("receipt lists {} files but {} were reported", receipt.file_count, file_count);
+        }
+        Ok(())
+    }
+
+    fn release(&self, node_id: NodeId) {
+        let mut claimed = self.claimed.lock().unwrap();
+        if *claimed == Some(node_id) {
+            *claimed = None;
+        }
+    }
+
+    async fn serve(&self, node_id: NodeId, send: &mut SendStream, recv: &mut RecvStream) -> Result<()> {
+        let ControlMessage::Hello {
+            version,
+            agentbeam_version,
+            capabilities,
+            collection,
+            display_name,
+        } = tokio::time::timeout(HANDSHAKE_TIMEOUT, read_message(recv))
+            .await
+            .with_context(|| format!("Peer {} did not send a handshake", node_id))??
+        else {
+            write_message(send, &ControlMessage::Decline { reason: ErrorReason::Protocol }).await?;
+            anyhow::bail!("Peer {} did not start with a handshake", node_id);
+        };
+        debug!(
+            "Handshake from {} (AgentBeam {}, capabilities {:?})",
+            node_id, agentbeam_version, capabilities
+        );
+
+        let missing = self
+            .required
+            .iter()
+            .find(|required| !capabilities.iter().any(|capability| capability == **required));
+        let reason = match missing {
+            Some(capability) => {
+                debug!("Peer {} lacks the {} capability", node_id, capability);
+                Some(ErrorReason::UnsupportedVersion)
+            }
+            None => self.admit(node_id, version, collection),
+        };
+        if let Some(reason) = reason {
+            write_message(send, &ControlMessage::Decline { reason }).await?;
+            self.events.send(ControlEvent::Declined { node_id, reason }).await.ok();
+            return Ok(());
+        }
+
+        // The monitor must know the peer before its blob connection arrives
+        self.events
+            .send(ControlEvent::Accepted { node_id, display_name })
+            .await
+            .ok();
+        write_message(send, &ControlMessage::Accept {
+            version: PROTOCOL_VERSION,
+            agentbeam_version: env!("CARGO_PKG_VERSION").to_string(),
+            capabilities: CAPABILITIES.iter().map(|c| c.to_string()).collect(),
+        })
+        .await?;
+
+        // A stalled receiver must not hold the beam forever
+        let reply = match tokio::time::timeout(COMPLETION_TIMEOUT, read_message(recv)).await {
+            Ok(reply) => reply,
+            Err(_) => Err(anyhow::anyhow!("no result within {} minutes", COMPLETION_TIMEOUT.as_secs() / 60)),
+        };
+        let event = match reply {
+            Ok(ControlMessage::Complete { root_hash, file_count, receipt }) if root_hash == self.collection => {
+                match receipt.as_ref().map(|receipt| self.check_receipt(node_id, file_count, receipt)) {
+                    Some(Err(err)) => ControlEvent::Failed {
+                        node_id,
+                        message: format!("invalid delivery receipt: {}", err),
+                    },
+                    _ => {
+                        write_message(send, &ControlMessage::Ack).await?;
+                        ControlEvent::Completed { node_id, root_hash, file_count, receipt }
+                    }
+                }
+            }
+            Ok(ControlMessage::Complete { root_hash, .. }) => ControlEvent::Failed {
+                node_id,
+                message: format!("receiver verified a different root hash {}", root_hash),
+            },
+            Ok(ControlMessage::Failed { reason, message }) => {
+                write_message(send, &ControlMessage::Ack).await?;
+                ControlEvent::Failed {
+                    node_id,
+                    message: format!("{} ({})", reason, message),
+                }
+            }
+            Ok(other) => ControlEvent::Failed {
+                node_id,
+                message: format!("unexpected message {:?}", other),
+            },
+            Err(err) => ControlEvent::Failed {
+                node_id,
+                message: format!("stopped before completing ({})", err),
+            },
+        };
+
+        if matches!(event, ControlEvent::Failed { .. }) {
+            self.release(node_id);
+        }
+        self.events.send(event).await.ok();
+        Ok(())
+    }
+}
+
+impl ProtocolHandler for ControlProtocol {
+    async fn accept(&self, connection: Connection) -> Result<(), AcceptError> {
+        let node_id = connection.remote_node_id()?;
+        let (mut send, mut recv) = connection.accept_bi().await?;
+
+        self.serve(node_id, &mut send, &mut recv)
+            .await
+            .map_err(|err| AcceptError::User { source: err.into() })?;
+
+        send.finish()?;
+        connection.closed().await;
+        Ok(())
+    }
+}
+
+/// Whether the sender's TLS stack turned down the control ALPN, as senders without `agentbeam/1` do
+fn is_alpn_rejected(err: &ConnectError) -> bool {
+    match err {
+        ConnectError::Connection { source, .. } => matches!(
+            &**source,
+            ConnectionError::ConnectionClosed(close)
+                if close.error_code == TransportErrorCode::crypto(NO_APPLICATION_PROTOCOL)
+        ),
+        _ => false,
+    }
+}
+
+/// Receiver side of `agentbeam/1`, held open for the whole transfer
+pub struct ControlClient {
+    connection: Connection,
+    send: SendStream,
+    recv: RecvStream,
+    /// Capabilities the sender announced
+    pub capabilities: Vec<String>,
+}
+
+impl ControlClient {
+    /// Handshake with the sender of `ticket`
+    ///
+    /// Returns `None` when the sender predates the control protocol, i.e. rejects its ALPN. Any other
+    /// connection failure, and a declined handshake, is an error naming the reason.
+    pub async fn connect(endpoint: &Endpoint, ticket: &BlobTicket) -> Result<Option<Self>> {
+        let connection = match endpoint.connect(ticket.node_addr().clone(), CONTROL_ALPN).await {
+            Ok(connection) => connection,
+            Err(err) if is_alpn_rejected(&err) => {
+                debug!("Sender does not accept {}: {}", String::from_utf8_lossy(CONTROL_ALPN), err);
+                return Ok(None);
+            }
+            Err(err) => return Err(err).context("Could not connect to the sender"),
+        };
+        let (mut send, mut recv) = connection.open_bi().await?;
+
+        write_message(&mut send, &ControlMessage::Hello {
+            version: PROTOCOL_VERSION,
+            agentbeam_version: env!("CARGO_PKG_VERSION").to_string(),
+            capabilities: CAPABILITIES.iter().map(|c| c.to_string()).collect(),
+            collection: ticket.hash(),
+            display_name: local_display_name(),
+        })
+        .await?;
+
+        match read_message(&mut recv).await? {
+            ControlMessage::Accept { agentbeam_version, capabilities, .. } => {
+                debug!("Sender runs AgentBeam {} with {:?}", agentbeam_version, capabilities);
+                Ok(Some(Self { connection, send, recv, capabilities }))
+            }
+            ControlMessage::Decline { reason } => {
+                anyhow::bail!("The sender declined the beam: {}", reason)
+            }
+            other => anyhow::bail!("Unexpected reply to handshake: {:?}", other),
+        }
+    }
+
+    /// Report the verified root hash with a signed receipt and wait for the sender to acknowledge it
+    pub async fn complete(mut self, root_hash: Hash, file_count: u64, receipt: SignedReceipt) -> Result<()> {
+        self.finish(ControlMessage::Complete {
+            root_hash,
+            file_count,
+            receipt: Some(receipt),
+        })
+        .await
+    }
+
+    pub async fn fail(mut self, reason: ErrorReason, message: String) -> Result<()> {
+        self.finish(ControlMessage::Failed { reason, message }).await
+    }
+
+    async fn finish(&mut self, message: ControlMessage) -> Result<()> {
+        write_message(&mut self.send, &message).await?;
+        let reply = read_message(&mut self.recv).await?;
+        if reply != ControlMessage::Ack {
+            anyhow::bail!("Sender did not acknowledge the beam: {:?}", reply);
+        }
+        self.send.finish()?;
+        self.connection.close(0u32.into(), b"done");
+        Ok(())
+    }
+}
+
+/// Name shown to the other side, from `$USER` or `$USERNAME`
+pub fn local_display_name() -> Option<String> {
+    std::env::var("USER").or_else(|_| std::env::var("USERNAME")).ok()
+}
+
+async fn write_message(send: &mut SendStream, message: &ControlMessage) -> Result<()> {
+    let bytes = serde_json::to_vec(message)?;
+    send.write_all(&(bytes.len() as u32).to_be_bytes()).await?;
+    send.write_all(&bytes).await?;
+    Ok(())
+}
+
+async fn read_message(recv: &mut RecvStream) -> Result<ControlMessage> {
+    let mut len = [0u8; 4];
+    recv.read_exact(&mut len).await.context("Control stream closed")?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_MESSAGE_SIZE {
+        anyhow::bail!("Control message of {} bytes exceeds the limit", len);
+    }
+
+    let mut bytes = vec![0u8; len];
+    recv.read_exact(&mut bytes).await.context("Control stream closed")?;
+    serde_json::from_slice(&bytes).context("Malformed control message")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iroh::{SecretKey, Watcher};
+
+    #[test]
+    fn test_admits_one_receiver_for_the_shared_collection() {
+        let (events, _rx) = mpsc::channel(8);
+        let collection = Hash::new(b"collection");
+        let sender = SecretKey::from_bytes(&rand::random::<[u8; 32]>()).public();
+        let control = ControlProtocol::new(sender, collection, Some(unix_now() + 3600), events);
+        let alice = SecretKey::from_bytes(&rand::random::<[u8; 32]>()).public();
+        let bob = SecretKey::from_bytes(&rand::random::<[u8; 32]>()).public();
+
+        assert_eq!(control.admit(alice, 2, collection), Some(ErrorReason::UnsupportedVersion));
+        assert_eq!(control.admit(alice, PROTOCOL_VERSION, Hash::new(b"other")), Some(ErrorReason::UnknownCollection));
+        assert_eq!(control.admit(alice, PROTOCOL_VERSION, collection), None);
+        assert_eq!(control.admit(bob, PROTOCOL_VERSION, collection), Some(ErrorReason::NotAuthorized));
+
+        control.release(alice);
+        assert_eq!(control.admit(bob, PROTOCOL_VERSION, collection), None);
+
+        let (events, _rx) = mpsc::channel(8);
+        let expired = ControlProtocol::new(sender, collection, Some(unix_now() - 1), events);
+        assert_eq!(expired.admit(alice, PROTOCOL_VERSION, collection), Some(ErrorReason::Expired));
+
+        let json = serde_json::to_string(&ControlMessage::Decline { reason: ErrorReason::Expired }).unwrap();
+        assert_eq!(json, r#"{"type":"decline","reason":"expired"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_only_when_the_sender_lacks_the_alpn() {
+        let bind = || Endpoint::builder().relay_mode(iroh::RelayMode::Disabled);
+        let sender = bind().alpns(vec![b"other/1".to_vec()]).bind().await.unwrap();
+        let receiver = bind().bind().await.unwrap();
+        let accept_loop = {
+            let sender = sender.clone();
+            tokio::spawn(async move {
+                while let Some(incoming) = sender.accept().await {
+                    let _ = incoming.await;
+                }
+            })
+        };
+
+        let mut addr = sender.node_addr().initialized().await;
+        let ticket = BlobTicket::new(addr.clone(), Hash::new(b"collection"), iroh_blobs::BlobFormat::HashSeq);
+        assert!(ControlClient::connect(&receiver, &ticket).await.unwrap().is_none());
+
+        // A sender that cannot be reached is an error, not an older AgentBeam
+        sender.close().await;
+        accept_loop.abort();
+        addr.direct_addresses = ["127.0.0.1:9".parse().unwrap()].into();
+        let ticket = BlobTicket::new(addr, Hash::new(b"collection"), iroh_blobs::BlobFormat::HashSeq);
+        let result = tokio::time::timeout(Duration::from_secs(5), ControlClient::connect(&receiver, &ticket)).await;
+        assert!(!matches!(result, Ok(Ok(_))));
+    }
+}
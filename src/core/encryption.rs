@@ -0,0 +1,334 @@
+use anyhow::{anyhow, Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::stream::{DecryptorBE32, EncryptorBE32};
+use chacha20poly1305::aead::KeyInit;
+use chacha20poly1305::{Key, XChaCha20Poly1305};
+use iroh_blobs::{api::Store, format::collection::Collection};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// Collection entry describing how the beam is encrypted; the only entry left in plaintext
+pub const ENCRYPTION_PATH: &str = ".agentbeam-encryption.json";
+
+/// Encrypted collection entry listing the real paths of the entries after it
+pub const INDEX_PATH: &str = ".agentbeam-index";
+
+/// Read instead of prompting, for scripts
+pub const PASSPHRASE_ENV: &str = "AGENTBEAM_PASSPHRASE";
+
+const ENCRYPTION_VERSION: u32 = 1;
+const KDF_ARGON2ID: &str = "argon2id";
+const CIPHER_XCHACHA20POLY1305: &str = "xchacha20poly1305-stream-be32";
+
+/// Argon2id cost for new beams: 64 MiB and 3 passes
+const DEFAULT_MEMORY_KIB: u32 = 64 * 1024;
+const DEFAULT_ITERATIONS: u32 = 3;
+const DEFAULT_PARALLELISM: u32 = 1;
+
+/// Refuse headers that would make the receiver allocate more than 1 GiB for the key
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+
+/// Plaintext bytes per AEAD chunk
+const CHUNK_SIZE: u32 = 64 * 1024;
+const MAX_CHUNK_SIZE: u32 = 16 * 1024 * 1024;
+
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+/// XChaCha20's 24 byte nonce minus the 5 bytes STREAM uses for the counter and last flag
+const NONCE_PREFIX_LEN: usize = 19;
+const TAG_LEN: usize = 16;
+
+const MIN_PASSPHRASE_LEN: usize = 8;
+const MAX_ATTEMPTS: u32 = 3;
+
+/// How the key is derived from the passphrase
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub algorithm: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    /// Hex random salt, new for every beam
+    pub salt: String,
+}
+
+/// Everything but the passphrase needed to decrypt a beam
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptionHeader {
+    pub version: u32,
+    pub kdf: KdfParams,
+    pub cipher: String,
+    pub chunk_size: u32,
+}
+
+/// A passphrase-derived key that encrypts blobs before import and decrypts them on export
+///
+/// Each blob is a random nonce prefix followed by the STREAM construction over fixed size chunks,
+/// so truncated, reordered or altered chunks fail to decrypt.
+pub struct BeamCipher {
+    aead: XChaCha20Poly1305,
+    header: EncryptionHeader,
+}
+
+impl BeamCipher {
+    /// Derive a key for a new beam with a fresh salt
+    pub fn new(passphrase: &str) -> Result<Self> {
+        let header = EncryptionHeader {
+            version: ENCRYPTION_VERSION,
+            kdf: KdfParams {
+                algorithm: KDF_ARGON2ID.to_string(),
+                memory_kib: DEFAULT_MEMORY_KIB,
+                iterations: DEFAULT_ITERATIONS,
+                parallelism: DEFAULT_PARALLELISM,
+                salt: hex::encode(rand::random::<[u8; SALT_LEN]>()),
+            },
+            cipher: CIPHER_XCHACHA20POLY1305.to_string(),
+            chunk_size: CHUNK_SIZE,
+        };
+        Self::from_header(header, passphrase)
+    }
+
+    /// Derive the key of a received beam
+    pub fn from_header(header: EncryptionHeader, passphrase: &str) -> Result<Self> {
+        if header.version != ENCRYPTION_VERSION
+            || header.kdf.algorithm != KDF_ARGON2ID
+            || header.cipher != CIPHER_XCHACHA20POLY1305
+        {
+            anyhow::bail!(
+                "Beam is encrypted with {} and {} (version {}), which this AgentBeam does not support\nUpgrade AgentBeam to receive it",
+                header.kdf.algorithm,
+                header.cipher,
+                header.version
+            );
+        }
+        if header.kdf.memory_kib > MAX_MEMORY_KIB || header.chunk_size == 0 || header.chunk_size > MAX_CHUNK_SIZE {
+            anyhow::bail!("Encryption header asks for unreasonable parameters");
+        }
+
+        let salt = hex::decode(&header.kdf.salt).context("Encryption salt is malformed")?;
+        let params = Params::new(header.kdf.memory_kib, header.kdf.iterations, header.kdf.parallelism, Some(KEY_LEN))
+            .map_err(|e| anyhow!("Invalid key derivation parameters: {}", e))?;
+        let mut key = [0u8; KEY_LEN];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|e| anyhow!("Key derivation failed: {}", e))?;
+
+        Ok(Self {
+            aead: XChaCha20Poly1305::new(Key::from_slice(&key)),
+            header,
+        })
+    }
+
+    pub fn header(&self) -> &EncryptionHeader {
+        &self.header
+    }
+
+    /// Encrypt `reader` into `writer`, returning the BLAKE3 hash of the plaintext
+    pub fn encrypt(&self, mut reader: impl Read, mut writer: impl Write) -> Result<blake3::Hash> {
+        let nonce = rand::random::<[u8; NONCE_PREFIX_LEN]>();
+        writer.write_all(&nonce)?;
+        let mut encryptor = EncryptorBE32::from_aead(self.aead.clone(), nonce.as_ref().into());
+        let mut hasher = blake3::Hasher::new();
+
+        // A chunk is only known to be the last once the next read comes back empty
+        let chunk_size = self.header.chunk_size as usize;
+        let mut chunk = read_chunk(&mut reader, chunk_size)?;
+        loop {
+            let next = if chunk.len() == chunk_size { read_chunk(&mut reader, chunk_size)? } else { Vec::new() };
+            if next.is_empty() {
+                break;
+            }
+            hasher.update(&chunk);
+            let sealed = encryptor.encrypt_next(chunk.as_slice()).map_err(|_| anyhow!("Encryption failed"))?;
+            writer.write_all(&sealed)?;
+            chunk = next;
+        }
+        hasher.update(&chunk);
+        let sealed = encryptor.encrypt_last(chunk.as_slice()).map_err(|_| anyhow!("Encryption failed"))?;
+        writer.write_all(&sealed)?;
+        writer.flush()?;
+
+        Ok(hasher.finalize())
+    }
+
+    /// Decrypt `reader` into `writer`; fails on a wrong key or any change to the ciphertext
+    pub fn decrypt(&self, mut reader: impl Read, mut writer: impl Write) -> Result<()> {
+        let mut nonce = [0u8; NONCE_PREFIX_LEN];
+        reader.read_exact(&mut nonce).context("Encrypted blob is truncated")?;
+        let mut decryptor = DecryptorBE32::from_aead(self.aead.clone(), nonce.as_ref().into());
+
+        let chunk_size = self.header.chunk_size as usize + TAG_LEN;
+        let mut chunk = read_chunk(&mut reader, chunk_size)?;
+        loop {
+            let next = if chunk.len() == chunk_size { read_chunk(&mut reader, chunk_size)? } else { Vec::new() };
+            if next.is_empty() {
+                break;
+            }
+            let plain = decryptor.decrypt_next(chunk.as_slice()).map_err(|_| decryption_error())?;
+            writer.write_all(&plain)?;
+            chunk = next;
+        }
+        let plain = decryptor.decrypt_last(chunk.as_slice()).map_err(|_| decryption_error())?;
+        writer.write_all(&plain)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Encrypt `source` into `target`, returning the hex BLAKE3 hash of the plaintext
+    pub fn encrypt_file(&self, source: &Path, target: &Path) -> Result<String> {
+        let reader = BufReader::new(File::open(source).with_context(|| format!("Failed to open {}", source.display()))?);
+        let writer = BufWriter::new(File::create(target)?);
+        Ok(self.encrypt(reader, writer)?.to_hex().to_string())
+    }
+
+    pub fn decrypt_file(&self, source: &Path, target: &Path) -> Result<()> {
+        let reader = BufReader::new(File::open(source)?);
+        let writer = BufWriter::new(File::create(target).with_context(|| format!("Failed to create {}", target.display()))?);
+        self.decrypt(reader, writer)
+    }
+
+    pub fn encrypt_bytes(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut sealed = Vec::with_capacity(NONCE_PREFIX_LEN + data.len() + TAG_LEN);
+        self.encrypt(data, &mut sealed)?;
+        Ok(sealed)
+    }
+
+    pub fn decrypt_bytes(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut plain = Vec::with_capacity(data.len());
+        self.decrypt(data, &mut plain)?;
+        Ok(plain)
+    }
+}
+
+/// Whether a collection was imported with a passphrase
+pub fn is_encrypted(collection: &Collection) -> bool {
+    collection.iter().next().is_some_and(|(name, _)| name == ENCRYPTION_PATH)
+}
+
+/// Ask for the passphrase of an encrypted collection and return its key and the real entry paths
+///
+/// The paths line up with the collection entries after the header and index.
+pub async fn unlock(store: &Store, collection: &Collection) -> Result<(BeamCipher, Vec<String>)> {
+    let entries: Vec<_> = collection.iter().collect();
+    let [(_, header_hash), (_, index_hash), ..] = entries.as_slice() else {
+        anyhow::bail!("Encrypted beam is missing its index");
+    };
+    let header: EncryptionHeader = serde_json::from_slice(&store.get_bytes(*header_hash).await?)
+        .context("Failed to parse encryption header")?;
+    let sealed_index = store.get_bytes(*index_hash).await?;
+
+    // Only a typed passphrase gets another try
+    let interactive = std::env::var(PASSPHRASE_ENV).is_err();
+    for attempt in 1..=MAX_ATTEMPTS {
+        // The prompt and the key derivation both block
+        let header = header.clone();
+        let cipher = tokio::task::spawn_blocking(move || {
+            let passphrase = read_passphrase("🔐 This beam is encrypted. Passphrase: ")?;
+            BeamCipher::from_header(header, &passphrase)
+        })
+        .await??;
+        match cipher.decrypt_bytes(&sealed_index) {
+            Ok(index) => {
+                let names: Vec<String> = serde_json::from_slice(&index).context("Failed to parse encrypted index")?;
+                if names.len() != entries.len() - 2 {
+                    anyhow::bail!("Encrypted index lists {} files but the beam has {}", names.len(), entries.len() - 2);
+                }
+                return Ok((cipher, names));
+            }
+            Err(_) if interactive && attempt < MAX_ATTEMPTS => println!("Wrong passphrase, try again"),
+            Err(_) => break,
+        }
+    }
+    anyhow::bail!("Wrong passphrase, or the beam was changed after it was encrypted")
+}
+
+/// Read a passphrase from `$AGENTBEAM_PASSPHRASE` or the terminal, without echo
+pub fn read_passphrase(prompt: &str) -> Result<String> {
+    if let Ok(passphrase) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    rpassword::prompt_password(prompt).context("Failed to read passphrase")
+}
+
+/// Ask for a passphrase for a new beam, twice unless it comes from the environment
+pub fn read_new_passphrase() -> Result<String> {
+    let passphrase = read_passphrase("🔐 Passphrase for this beam: ")?;
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        anyhow::bail!("Passphrase must be at least {} characters", MIN_PASSPHRASE_LEN);
+    }
+    if std::env::var(PASSPHRASE_ENV).is_err() {
+        let repeated = rpassword::prompt_password("   Repeat passphrase: ").context("Failed to read passphrase")?;
+        if repeated != passphrase {
+            anyhow::bail!("Passphrases do not match");
+        }
+    }
+    Ok(passphrase)
+}
+
+/// Read up to `len` bytes, fewer only at the end of the input
+fn read_chunk(reader: &mut impl Read, len: usize) -> Result<Vec<u8>> {
+    let mut chunk = Vec::with_capacity(len);
+    reader.take(len as u64).read_to_end(&mut chunk)?;
+    Ok(chunk)
+}
+
+fn decryption_error() -> anyhow::Error {
+    anyhow!("Decryption failed: wrong passphrase or altered data")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cheap_header(chunk_size: u32) -> EncryptionHeader {
+        EncryptionHeader {
+            version: ENCRYPTION_VERSION,
+            kdf: KdfParams {
+                algorithm: KDF_ARGON2ID.to_string(),
+                memory_kib: 1024,
+                iterations: 1,
+                parallelism: 1,
+                salt: hex::encode([7u8; SALT_LEN]),
+            },
+            cipher: CIPHER_XCHACHA20POLY1305.to_string(),
+            chunk_size,
+        }
+    }
+
+    #[test]
+    fn test_cipher_round_trips_chunks_and_rejects_tampering() {
+        let cipher = BeamCipher::from_header(cheap_header(16), "correct horse").unwrap();
+
+        // Empty, shorter than a chunk, exactly two chunks, and a partial last chunk
+        for len in [0, 5, 32, 45] {
+            let data: Vec<u8> = (0..len as u8).collect();
+            let sealed = cipher.encrypt_bytes(&data).unwrap();
+            assert_eq!(sealed.len(), NONCE_PREFIX_LEN + len + len.div_ceil(16).max(1) * TAG_LEN);
+            assert_eq!(cipher.decrypt_bytes(&sealed).unwrap(), data);
+        }
+
+        let data = b"fn main() { println!(\"secret\"); }".to_vec();
+        let sealed = cipher.encrypt_bytes(&data).unwrap();
+
+        let wrong = BeamCipher::from_header(cheap_header(16), "wrong horse").unwrap();
+        assert!(wrong.decrypt_bytes(&sealed).is_err());
+
+        // Dropping the last chunk must not pass as a shorter file
+        let truncated = &sealed[..NONCE_PREFIX_LEN + 2 * (16 + TAG_LEN)];
+        assert!(cipher.decrypt_bytes(truncated).is_err());
+
+        let mut flipped = sealed.clone();
+        flipped[NONCE_PREFIX_LEN + 3] ^= 1;
+        assert!(cipher.decrypt_bytes(&flipped).is_err());
+
+        let hash = cipher.encrypt(data.as_slice(), std::io::sink()).unwrap();
+        assert_eq!(hash, blake3::hash(&data));
+
+        let mut unsupported = cheap_header(16);
+        unsupported.cipher = "rot13".to_string();
+        assert!(BeamCipher::from_header(unsupported, "correct horse").is_err());
+    }
+}
@@ -1,24 +1,67 @@
 use anyhow::{Context, Result};
 use futures::StreamExt;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use ignore::WalkBuilder;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use iroh::SecretKey;
 use iroh_blobs::{
     format::collection::Collection,
-    api::{blobs::{AddPathOptions, ImportMode, ExportMode, ExportOptions}, Store, TempTag},
-    BlobsProtocol, BlobFormat,
+    api::{blobs::{AddPathOptions, ImportMode, ExportMode, ExportOptions}, TempTag},
+    BlobsProtocol, BlobFormat, Hash,
 };
+use std::collections::{BTreeSet, HashSet};
 use std::path::{Path, PathBuf};
-use tracing::{debug, trace};
+use tracing::{debug, info, trace};
 
 use crate::core::config::{BeamMetadata, WARN_THRESHOLD};
+use crate::core::encryption::{BeamCipher, ENCRYPTION_PATH, INDEX_PATH};
+use crate::core::manifest::{self, BeamManifest, ManifestEntry, MANIFEST_PATH, METADATA_PATH};
+use crate::core::secret_scanner::{SecretFinding, SecretScanner};
 
 pub struct FileCollector {
     root_path: PathBuf,
+    encryption: Option<Encryption>,
+}
+
+/// Key for a passphrase-protected beam, and where its ciphertext is staged before import
+struct Encryption {
+    cipher: BeamCipher,
+    scratch_dir: PathBuf,
+}
+
+/// Repository paths a beam leaves out because a `.beamignore` matches them
+///
+/// Secret scan exclusions are appended to the root `.beamignore`, so they are covered too.
+pub struct WithheldPaths {
+    /// Each `.beamignore` with the directory it applies to, relative to the root
+    beamignores: Vec<(String, Gitignore)>,
+}
+
+impl WithheldPaths {
+    pub fn contains(&self, path: &str) -> bool {
+        self.beamignores.iter().any(|(dir, beamignore)| {
+            let relative = if dir.is_empty() {
+                Some(path)
+            } else {
+                path.strip_prefix(dir.as_str()).and_then(|rest| rest.strip_prefix('/'))
+            };
+            relative.is_some_and(|relative| beamignore.matched_path_or_any_parents(relative, false).is_ignore())
+        })
+    }
 }
 
 impl FileCollector {
     pub fn new(root_path: PathBuf) -> Self {
-        Self { root_path }
+        Self {
+            root_path,
+            encryption: None,
+        }
+    }
+
+    /// Encrypt every blob with `cipher` before import, staging the ciphertext in `scratch_dir`
+    pub fn with_encryption(mut self, cipher: BeamCipher, scratch_dir: PathBuf) -> Self {
+        self.encryption = Some(Encryption { cipher, scratch_dir });
+        self
     }
 
     pub fn collect_files(&self) -> Result<Vec<(String, PathBuf)>> {
@@ -28,12 +71,14 @@ impl FileCollector {
             .git_global(false)
             .git_exclude(false)
             .hidden(false)
+            // Git history travels as a bundle, never as raw `.git` files
+            .filter_entry(|entry| entry.file_name() != ".git")
             .build();
 
         let mut files = Vec::new();
         for entry in walker {
             let entry = entry?;
-            if entry.file_type().map_or(false, |ft| ft.is_file()) {
+            if entry.file_type().is_some_and(|ft| ft.is_file()) {
                 let path = entry.path();
                 let relative = path
                     .strip_prefix(&self.root_path)
@@ -58,16 +103,100 @@ impl FileCollector {
         Ok(files)
     }
 
+    /// Scan collected files for credentials before they are imported
+    pub fn scan_for_secrets(&self, files: &[(String, PathBuf)]) -> Result<Vec<SecretFinding>> {
+        SecretScanner::scan_files(files)
+    }
+
+    /// Remove files with secret findings from the list and append them to .beamignore
+    pub fn exclude_findings(
+        &self,
+        files: &mut Vec<(String, PathBuf)>,
+        findings: &[SecretFinding],
+    ) -> Result<Vec<String>> {
+        let excluded: BTreeSet<String> = findings
+            .iter()
+            .map(|f| f.relative_path.clone())
+            .collect();
+
+        if excluded.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        files.retain(|(relative, _)| !excluded.contains(relative));
+
+        let beamignore_path = self.root_path.join(".beamignore");
+        let mut content = if beamignore_path.exists() {
+            std::fs::read_to_string(&beamignore_path)?
+        } else {
+            String::new()
+        };
+
+        let existing: BTreeSet<String> = content.lines().map(|l| l.trim().to_string()).collect();
+        let new_entries: Vec<&String> = excluded
+            .iter()
+            .filter(|path| !existing.contains(&format!("/{}", path)))
+            .collect();
+
+        if !new_entries.is_empty() {
+            if !content.is_empty() && !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content.push_str("\n# Excluded by AgentBeam secret scan\n");
+            for path in &new_entries {
+                content.push_str(&format!("/{}\n", path));
+            }
+            std::fs::write(&beamignore_path, content)?;
+            info!("Added {} files to .beamignore", new_entries.len());
+        }
+
+        Ok(excluded.into_iter().collect())
+    }
+
+    /// Paths this workspace leaves out of a beam, for checking what committed history would carry
+    pub fn withheld_paths(&self) -> Result<WithheldPaths> {
+        let mut beamignores = Vec::new();
+        for (name, path) in self.collect_files()? {
+            let Some(dir) = name.strip_suffix(".beamignore") else {
+                continue;
+            };
+            let dir = match dir.strip_suffix('/') {
+                Some(dir) => dir,
+                None if dir.is_empty() => dir,
+                None => continue,
+            };
+            let mut builder = GitignoreBuilder::new(path.parent().unwrap_or(&self.root_path));
+            if let Some(e) = builder.add(&path) {
+                return Err(e).with_context(|| format!("Failed to read {}", name));
+            }
+            beamignores.push((dir.to_string(), builder.build()?));
+        }
+        Ok(WithheldPaths { beamignores })
+    }
+
+    /// Import `files`, the metadata and a manifest signed with `secret_key` into one collection
+    ///
+    /// Returns the collection's tag, the total size of `files` and the signed manifest JSON. With
+    /// encryption, entries are imported as ciphertext under their index, their paths are kept in an
+    /// encrypted index, and the manifest still describes the plaintext.
     pub async fn create_collection(
         &self,
         blobs: &BlobsProtocol,
         files: Vec<(String, PathBuf)>,
-        metadata: BeamMetadata,
+        mut metadata: BeamMetadata,
+        secret_key: &SecretKey,
         mp: Option<&MultiProgress>,
-    ) -> Result<(TempTag, u64, Collection)> {
+    ) -> Result<(TempTag, u64, Vec<u8>)> {
         let file_count = files.len();
         let mut total_size = 0u64;
 
+        let session_files: HashSet<String> = metadata
+            .agent_sessions
+            .iter()
+            .flat_map(|session| session.files.iter().cloned())
+            .collect();
+        let mut entries = Vec::with_capacity(file_count);
+
         let pb = mp.map(|mp| {
             let pb = mp.add(ProgressBar::new(file_count as u64));
             pb.set_style(
@@ -81,6 +210,9 @@ impl FileCollector {
         });
 
         let mut collection_items = Vec::new();
+        if let Some(encryption) = &self.encryption {
+            std::fs::create_dir_all(&encryption.scratch_dir)?;
+        }
         
         for (i, (relative_path, file_path)) in files.into_iter().enumerate() {
             if let Some(ref pb) = pb {
@@ -88,7 +220,8 @@ impl FileCollector {
                 pb.set_message(format!("Importing {}", relative_path));
             }
 
-            let file_size = std::fs::metadata(&file_path)?.len();
+            let file_metadata = std::fs::metadata(&file_path)?;
+            let file_size = file_metadata.len();
             total_size += file_size;
 
             debug!("Adding file: {} ({}bytes)", relative_path, file_size);
@@ -104,8 +237,18 @@ impl FileCollector {
             // the problematic .agentbeam-* directories
             let import_mode = ImportMode::TryReference;
 
+            // Ciphertext is staged next to the other derived files; the manifest hashes the plaintext
+            let (import_path, plaintext_hash) = match &self.encryption {
+                Some(encryption) => {
+                    let encrypted_path = encryption.scratch_dir.join(i.to_string());
+                    let hash = encryption.cipher.encrypt_file(&abs_path, &encrypted_path)?;
+                    (encrypted_path, Some(hash))
+                }
+                None => (abs_path, None),
+            };
+
             let add_options = AddPathOptions {
-                path: abs_path,
+                path: import_path,
                 mode: import_mode,
                 format: BlobFormat::Raw,
             };
@@ -125,6 +268,14 @@ impl FileCollector {
                 }
             };
 
+            // Raw blob hashes are the BLAKE3 hashes of the content
+            entries.push(ManifestEntry {
+                path: relative_path.clone(),
+                size: file_size,
+                hash: plaintext_hash.unwrap_or_else(|| tag.hash().to_hex()),
+                mode: manifest::file_mode(&file_metadata),
+                role: manifest::role_of(&relative_path, &session_files),
+            });
             collection_items.push((relative_path, *tag.hash()));
         }
 
@@ -133,18 +284,56 @@ impl FileCollector {
         }
 
         // Add metadata to the collection
+        metadata.total_size = total_size;
+        metadata.file_count = file_count;
         let metadata_json = serde_json::to_vec(&metadata)?;
-        let metadata_tag = blobs.add_slice(&metadata_json).await?;
-        collection_items.push((".agentbeam-metadata.json".to_string(), metadata_tag.hash));
+        let metadata_tag = blobs.add_slice(&self.seal(&metadata_json)?).await?;
+        collection_items.push((METADATA_PATH.to_string(), metadata_tag.hash));
+
+        // Sign every entry so the receiver can check the beam offline
+        let manifest = BeamManifest {
+            sender: secret_key.public().to_string(),
+            created_at: metadata.created_at,
+            total_size,
+            metadata_hash: blake3::hash(&metadata_json).to_hex().to_string(),
+            entries,
+        };
+        let signed = manifest.sign(secret_key)?;
+        let manifest_json = serde_json::to_vec_pretty(&signed)?;
+        let manifest_tag = blobs.add_slice(&self.seal(&manifest_json)?).await?;
+        collection_items.push((MANIFEST_PATH.to_string(), manifest_tag.hash));
+
+        // Only the encryption header and opaque entry numbers are left readable
+        if let Some(encryption) = &self.encryption {
+            let names: Vec<&String> = collection_items.iter().map(|(name, _)| name).collect();
+            let index = encryption.cipher.encrypt_bytes(&serde_json::to_vec(&names)?)?;
+            let index_tag = blobs.add_slice(&index).await?;
+            let header_tag = blobs.add_slice(&serde_json::to_vec(encryption.cipher.header())?).await?;
+
+            let mut sealed_items = vec![
+                (ENCRYPTION_PATH.to_string(), header_tag.hash),
+                (INDEX_PATH.to_string(), index_tag.hash),
+            ];
+            sealed_items.extend(collection_items.into_iter().enumerate().map(|(i, (_, hash))| (i.to_string(), hash)));
+            collection_items = sealed_items;
+        }
 
         let collection = Collection::from_iter(collection_items);
-        let collection_tag = collection.clone().store(blobs.store()).await?;
+        let collection_tag = collection.store(blobs.store()).await?;
 
         if let Some(pb) = pb {
             pb.finish_with_message(format!("✓ Imported {} files", file_count));
         }
 
-        Ok((collection_tag, total_size, collection))
+        Ok((collection_tag, total_size, manifest_json))
+    }
+
+    /// Encrypt `data` if this beam is encrypted
+    fn seal(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match &self.encryption {
+            Some(encryption) => encryption.cipher.encrypt_bytes(data),
+            None => Ok(data.to_vec()),
+        }
     }
 
     pub async fn export_collection(
@@ -153,6 +342,9 @@ impl FileCollector {
         target_dir: &Path,
         mp: Option<&MultiProgress>,
     ) -> Result<()> {
+        // Names come from the sender; check them all before anything is written
+        check_entry_names(collection.iter().map(|(name, _)| name.as_str()))
+            .context("Collection has an unsafe entry")?;
         std::fs::create_dir_all(target_dir)?;
 
         let pb = mp.map(|mp| {
@@ -188,26 +380,8 @@ impl FileCollector {
                 std::fs::create_dir_all(parent)?;
             }
 
-            let mut stream = blobs.store()
-                .export_with_opts(ExportOptions {
-                    hash: *hash,
-                    target: target_path.clone(),
-                    mode: ExportMode::TryReference,
-                })
-                .stream()
-                .await;
-
-            while let Some(progress) = stream.next().await {
-                use iroh_blobs::api::blobs::ExportProgressItem::*;
-                match progress {
-                    Done => {
-                        trace!("Exported {} to {}", name, target_path.display());
-                        break;
-                    }
-                    Error(e) => return Err(e.into()),
-                    _ => {}
-                }
-            }
+            Self::export_blob(blobs, *hash, &target_path, ExportMode::TryReference).await?;
+            trace!("Exported {} to {}", name, target_path.display());
         }
 
         if let Some(pb) = pb {
@@ -216,4 +390,149 @@ impl FileCollector {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Export an encrypted collection, decrypting each entry to its path from the unlocked index
+    pub async fn export_encrypted(
+        blobs: &BlobsProtocol,
+        collection: &Collection,
+        names: &[String],
+        cipher: &BeamCipher,
+        target_dir: &Path,
+        mp: Option<&MultiProgress>,
+    ) -> Result<()> {
+        check_encrypted_index(names, collection.len())?;
+        std::fs::create_dir_all(target_dir)?;
+        let target_dir = if target_dir.is_absolute() {
+            target_dir.to_path_buf()
+        } else {
+            std::env::current_dir()?.join(target_dir)
+        };
+
+        let pb = mp.map(|mp| {
+            let pb = mp.add(ProgressBar::new(names.len() as u64));
+            pb.set_style(
+                ProgressStyle::default_bar()
+                    .template("[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} {msg}")
+                    .unwrap()
+                    .progress_chars("█▉▊▋▌▍▎▏  "),
+            );
+            pb.set_message("Decrypting files...");
+            pb
+        });
+
+        // Ciphertext lands here first and never under its real name
+        let ciphertext_path = target_dir.join(".agentbeam-ciphertext");
+        // The header and index come first; the rest line up with `names`
+        for (i, (name, (_, hash))) in names.iter().zip(collection.iter().skip(2)).enumerate() {
+            if let Some(ref pb) = pb {
+                pb.set_position(i as u64);
+                pb.set_message(format!("Decrypting {}", name));
+            }
+
+            let target_path = target_dir.join(name);
+            if let Some(parent) = target_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+
+            // Copied, since a referenced export would leave the store pointing at a deleted file
+            if let Err(err) = Self::export_blob(blobs, *hash, &ciphertext_path, ExportMode::Copy).await {
+                let _ = std::fs::remove_file(&ciphertext_path);
+                return Err(err);
+            }
+            let decrypted = cipher.decrypt_file(&ciphertext_path, &target_path);
+            std::fs::remove_file(&ciphertext_path)?;
+            if let Err(err) = decrypted {
+                // A partly decrypted file was never authenticated
+                let _ = std::fs::remove_file(&target_path);
+                return Err(err.context(format!("Failed to decrypt {}", name)));
+            }
+            trace!("Decrypted {} to {}", name, target_path.display());
+        }
+
+        if let Some(pb) = pb {
+            pb.finish_with_message(format!("✓ Decrypted {} files", names.len()));
+        }
+
+        Ok(())
+    }
+
+    async fn export_blob(blobs: &BlobsProtocol, hash: Hash, target: &Path, mode: ExportMode) -> Result<()> {
+        let mut stream = blobs.store()
+            .export_with_opts(ExportOptions {
+                hash,
+                target: target.to_path_buf(),
+                mode,
+            })
+            .stream()
+            .await;
+
+        while let Some(progress) = stream.next().await {
+            use iroh_blobs::api::blobs::ExportProgressItem::*;
+            match progress {
+                Done => break,
+                Error(e) => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fail on the first collection name that would land outside the target directory
+fn check_entry_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Result<()> {
+    names.into_iter().try_for_each(manifest::check_relative_path)
+}
+
+/// Check a decrypted index against a collection of `collection_len` entries
+///
+/// The index is only authenticated, not trusted: a crafted one could point anywhere, or list fewer
+/// names than there are payload entries so the rest are silently dropped.
+fn check_encrypted_index(names: &[String], collection_len: usize) -> Result<()> {
+    // The header and index come first; the rest line up with `names`
+    let payload = collection_len.saturating_sub(2);
+    if names.len() != payload {
+        anyhow::bail!("Encrypted index lists {} files but the beam has {}", names.len(), payload);
+    }
+    check_entry_names(names.iter().map(String::as_str)).context("Encrypted index has an unsafe entry")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_entry_names_stay_inside_the_target() {
+        assert!(check_entry_names(["src/lib.rs", ".agentbeam/claude-session.jsonl"]).is_ok());
+        assert!(check_entry_names(["src/lib.rs", "../../.bashrc"]).is_err());
+        assert!(check_entry_names(["/etc/passwd"]).is_err());
+        assert!(check_entry_names(["src/../../escape"]).is_err());
+    }
+
+    #[test]
+    fn test_withheld_paths_only_match_beamignore() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("web")).unwrap();
+        std::fs::write(root.join(".beamignore"), "/data.csv\n").unwrap();
+        std::fs::write(root.join("web/.beamignore"), "*.key\n").unwrap();
+        std::fs::write(root.join("notes.txt"), "shipped or not, never flagged").unwrap();
+
+        let withheld = FileCollector::new(root.to_path_buf()).withheld_paths().unwrap();
+        assert!(withheld.contains("data.csv"));
+        assert!(withheld.contains("web/tls/server.key"));
+        assert!(!withheld.contains("server.key"));
+        assert!(!withheld.contains("web/data.csv"));
+        assert!(!withheld.contains("notes.txt"));
+    }
+
+    #[test]
+    fn test_encrypted_index_must_match_the_collection() {
+        let names = vec!["src/lib.rs".to_string(), "README.md".to_string()];
+        assert!(check_encrypted_index(&names, 4).is_ok());
+        assert!(check_encrypted_index(&names, 5).is_err());
+        assert!(check_encrypted_index(&names, 3).is_err());
+        assert!(check_encrypted_index(&[], 1).is_ok());
+        assert!(check_encrypted_index(&["../escape".to_string()], 3).is_err());
+    }
+}
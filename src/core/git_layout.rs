@@ -0,0 +1,368 @@
+use anyhow::{Context, Result};
+use git2::{AttrCheckFlags, Oid, Repository, ResetType};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+use crate::core::git_repo::GitRepo;
+use crate::core::git_transfer::git;
+
+/// Collection directory for LFS objects shipped with `--include-lfs`, named by oid
+pub const LFS_PACKAGE_DIR: &str = ".agentbeam/lfs";
+
+const LFS_POINTER_PREFIX: &str = "version https://git-lfs.github.com/spec/v1";
+
+/// Index mode of a gitlink, the entry a submodule leaves in its superproject
+const GITLINK_MODE: u32 = 0o160000;
+
+/// A submodule pinned by the superproject
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubmoduleInfo {
+    pub path: String,
+    pub url: Option<String>,
+    /// Commit the superproject's index pins
+    pub sha: String,
+}
+
+/// A path tracked by Git LFS
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LfsFile {
+    pub path: String,
+    pub oid: Option<String>,
+    pub size: u64,
+    /// The working tree holds the real content rather than the pointer
+    pub materialized: bool,
+}
+
+/// Repository shape beyond a plain `.git` directory
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoLayout {
+    /// `.git` is a file pointing at a linked worktree's git dir
+    #[serde(default)]
+    pub worktree: bool,
+    #[serde(default)]
+    pub submodules: Vec<SubmoduleInfo>,
+    #[serde(default)]
+    pub lfs_files: Vec<LfsFile>,
+}
+
+impl RepoLayout {
+    /// Whether `workspace` is inside a git work tree, whether `.git` is a directory or a file
+    pub fn is_repository(workspace: &Path) -> bool {
+        matches!(GitRepo::discover(workspace), Ok(Some(repo)) if repo.workdir().is_some())
+    }
+
+    pub fn inspect(workspace: &Path) -> Result<Self> {
+        let Some(repo) = GitRepo::discover(workspace)? else {
+            return Ok(Self::default());
+        };
+        let Some(workdir) = repo.workdir() else {
+            return Ok(Self::default());
+        };
+
+        Ok(Self {
+            worktree: repo.is_worktree(),
+            submodules: Self::submodules(&repo)?,
+            lfs_files: Self::lfs_files(&repo, workdir)?,
+        })
+    }
+
+    fn submodules(repo: &GitRepo) -> Result<Vec<SubmoduleInfo>> {
+        let repository = repo.repository();
+
+        // URLs come from .gitmodules; a gitlink without an entry there has none
+        let urls: HashMap<String, String> = repository
+            .submodules()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|sm| Some((sm.path().to_string_lossy().to_string(), sm.url()?.to_string())))
+            .collect();
+
+        // Gitlinks in the index carry the pinned shas
+        let mut submodules = Vec::new();
+        for entry in repository.index()?.iter() {
+            if entry.mode != GITLINK_MODE {
+                continue;
+            }
+            let path = String::from_utf8_lossy(&entry.path).to_string();
+            submodules.push(SubmoduleInfo {
+                url: urls.get(&path).cloned(),
+                path,
+                sha: entry.id.to_string(),
+            });
+        }
+
+        Ok(submodules)
+    }
+
+    fn lfs_files(repo: &GitRepo, workdir: &Path) -> Result<Vec<LfsFile>> {
+        let repository = repo.repository();
+
+        let mut lfs_files = Vec::new();
+        for entry in repository.index()?.iter() {
+            if entry.mode == GITLINK_MODE {
+                continue;
+            }
+            let path = String::from_utf8_lossy(&entry.path).to_string();
+            let filter = repository.get_attr(Path::new(&path), "filter", AttrCheckFlags::FILE_THEN_INDEX)?;
+            if filter != Some("lfs") {
+                continue;
+            }
+
+            // The index holds the pointer whether or not the working tree is materialized
+            let pointer = repository
+                .find_blob(entry.id)
+                .map(|blob| String::from_utf8_lossy(blob.content()).to_string())
+                .unwrap_or_default();
+            let (oid, size) = parse_pointer(&pointer);
+
+            let materialized = match fs::read(workdir.join(&path)) {
+                Ok(content) => !content.starts_with(LFS_POINTER_PREFIX.as_bytes()),
+                Err(_) => false,
+            };
+
+            lfs_files.push(LfsFile {
+                path,
+                oid,
+                size,
+                materialized,
+            });
+        }
+
+        Ok(lfs_files)
+    }
+
+    /// LFS paths whose working tree holds only the pointer
+    pub fn lfs_pointers_only(&self) -> usize {
+        self.lfs_files.iter().filter(|f| !f.materialized).count()
+    }
+
+    /// Local LFS objects for pointer-only files, as collection entries
+    pub fn lfs_object_files(&self, workspace: &Path) -> Result<Vec<(String, PathBuf)>> {
+        let objects_dir = lfs_objects_dir(workspace)?;
+
+        let mut files = Vec::new();
+        for lfs_file in self.lfs_files.iter().filter(|f| !f.materialized) {
+            let Some(oid) = &lfs_file.oid else {
+                continue;
+            };
+            let object = object_path(&objects_dir, oid);
+            if object.is_file() {
+                files.push((format!("{}/{}", LFS_PACKAGE_DIR, oid), object));
+            } else {
+                debug!("LFS object for {} is not available locally", lfs_file.path);
+            }
+        }
+        Ok(files)
+    }
+}
+
+/// Put shipped LFS objects into the repository's store and materialize them if git-lfs is present
+///
+/// Only objects that hash to their oid and match the size in `lfs_files` are installed; the
+/// rest are skipped with a warning. Returns the number of objects installed.
+pub fn restore_lfs_objects(repo: &Path, package_dir: &Path, lfs_files: &[LfsFile]) -> Result<usize> {
+    let shipped = package_dir.join(LFS_PACKAGE_DIR);
+    if !shipped.is_dir() {
+        return Ok(0);
+    }
+
+    let sizes: HashMap<&str, u64> = lfs_files
+        .iter()
+        .filter_map(|file| file.oid.as_deref().map(|oid| (oid, file.size)))
+        .collect();
+    let objects_dir = lfs_objects_dir(repo)?;
+    let mut installed = 0;
+    for entry in fs::read_dir(&shipped)? {
+        let entry = entry?;
+        let oid = entry.file_name().to_string_lossy().to_string();
+        let Some(&size) = sizes.get(oid.as_str()) else {
+            warn!("Skipping LFS object {}: no pointer refers to it", oid);
+            continue;
+        };
+        if let Err(e) = verify_lfs_object(&entry.path(), &oid, size) {
+            warn!("Skipping LFS object {}: {:#}", oid, e);
+            continue;
+        }
+
+        let dest = object_path(&objects_dir, &oid);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(entry.path(), &dest)?;
+        installed += 1;
+    }
+
+    if installed > 0 && let Err(e) = git(repo, &["lfs", "checkout"]) {
+        warn!("Could not materialize LFS files: {}", e);
+    }
+    Ok(installed)
+}
+
+/// Turn received submodule directories back into repositories at their pinned shas
+///
+/// The working tree files are kept as received. Returns the paths that could not be restored.
+pub fn restore_submodules(repo: &Path, submodules: &[SubmoduleInfo]) -> Result<Vec<String>> {
+    let mut failed = Vec::new();
+
+    for submodule in submodules {
+        let dir = repo.join(&submodule.path);
+        if dir.join(".git").exists() {
+            continue;
+        }
+        fs::create_dir_all(&dir)?;
+
+        let restored = (|| -> Result<()> {
+            let url = submodule.url.as_deref().context("Submodule has no URL")?;
+            let repository = Repository::init(&dir)?;
+            let sha = Oid::from_str(&submodule.sha)?;
+            let mut origin = repository.remote("origin", url)?;
+            // libgit2 is built without network transports; those fetches need the binary
+            if let Err(e) = origin.fetch(&[submodule.sha.as_str()], None, None) {
+                debug!("In-process fetch of {} failed ({}); trying git", submodule.path, e);
+                git(&dir, &["fetch", "--quiet", "origin", &submodule.sha])?;
+            }
+            // Point HEAD at the pinned commit without touching the received files
+            repository.set_head_detached(sha)?;
+            let commit = repository.find_commit(sha)?;
+            repository.reset(commit.as_object(), ResetType::Mixed, None)?;
+            Ok(())
+        })();
+
+        if let Err(e) = restored {
+            warn!("Could not restore submodule {}: {}", submodule.path, e);
+            // A repository without commits cannot be added to the superproject; keep plain files
+            let _ = fs::remove_dir_all(dir.join(".git"));
+            failed.push(submodule.path.clone());
+        }
+    }
+
+    // Record the submodules in .git/config, like `git submodule init`
+    if !submodules.is_empty() {
+        let repository = Repository::open(repo)?;
+        for mut submodule in repository.submodules().unwrap_or_default() {
+            if let Err(e) = submodule.init(false) {
+                debug!("Could not initialize submodule {}: {}", submodule.path().display(), e);
+            }
+        }
+    }
+    Ok(failed)
+}
+
+fn lfs_objects_dir(repo: &Path) -> Result<PathBuf> {
+    let repo = GitRepo::discover(repo)?.with_context(|| format!("{} is not a git repository", repo.display()))?;
+    Ok(repo.common_dir().join("lfs").join("objects"))
+}
+
+/// Check that the file at `path` is the LFS object `oid` of `size` bytes
+fn verify_lfs_object(path: &Path, oid: &str, size: u64) -> Result<()> {
+    let actual_size = fs::metadata(path)?.len();
+    if actual_size != size {
+        anyhow::bail!("{} bytes, but its pointer says {}", actual_size, size);
+    }
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    let digest = hex::encode(hasher.finalize());
+    if digest != oid {
+        anyhow::bail!("content hashes to {}", digest);
+    }
+    Ok(())
+}
+
+fn object_path(objects_dir: &Path, oid: &str) -> PathBuf {
+    if oid.len() < 4 {
+        return objects_dir.join(oid);
+    }
+    objects_dir.join(&oid[0..2]).join(&oid[2..4]).join(oid)
+}
+
+fn parse_pointer(pointer: &str) -> (Option<String>, u64) {
+    let mut oid = None;
+    let mut size = 0;
+    for line in pointer.lines() {
+        if let Some(value) = line.strip_prefix("oid sha256:") {
+            oid = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("size ") {
+            size = value.trim().parse().unwrap_or(0);
+        }
+    }
+    (oid, size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_inspect_finds_submodules_and_lfs_pointers() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path();
+        git(repo, &["init", "--quiet"]).unwrap();
+
+        let oid = "4d7a214614ab2935c943f9e0ff69d22eadbb8f32b1258daaa5e2ca24d17e2393";
+        fs::write(repo.join(".gitattributes"), "*.bin filter=lfs diff=lfs merge=lfs -text\n").unwrap();
+        fs::write(
+            repo.join("model.bin"),
+            format!("{}\noid sha256:{}\nsize 12345\n", LFS_POINTER_PREFIX, oid),
+        )
+        .unwrap();
+        fs::write(repo.join(".gitmodules"), "[submodule \"vendor/lib\"]\n\tpath = vendor/lib\n\turl = https://example.com/lib.git\n").unwrap();
+        git(repo, &["add", ".gitattributes", "model.bin", ".gitmodules"]).unwrap();
+        let sha = "0123456789abcdef0123456789abcdef01234567";
+        git(repo, &["update-index", "--add", "--cacheinfo", &format!("160000,{},vendor/lib", sha)]).unwrap();
+
+        let layout = RepoLayout::inspect(repo).unwrap();
+        assert!(!layout.worktree);
+        assert_eq!(
+            layout.submodules,
+            vec![SubmoduleInfo {
+                path: "vendor/lib".to_string(),
+                url: Some("https://example.com/lib.git".to_string()),
+                sha: sha.to_string(),
+            }]
+        );
+        assert_eq!(layout.lfs_files.len(), 1);
+        assert_eq!(layout.lfs_files[0].oid.as_deref(), Some(oid));
+        assert_eq!(layout.lfs_files[0].size, 12345);
+        assert_eq!(layout.lfs_pointers_only(), 1);
+    }
+
+    #[test]
+    fn test_restore_lfs_objects_skips_unverified_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let repo = temp_dir.path();
+        git(repo, &["init", "--quiet"]).unwrap();
+
+        let content = b"model weights";
+        let oid = hex::encode(Sha256::digest(content));
+        let tampered = hex::encode(Sha256::digest(b"other weights"));
+        let shipped = repo.join(LFS_PACKAGE_DIR);
+        fs::create_dir_all(&shipped).unwrap();
+        fs::write(shipped.join(&oid), content).unwrap();
+        fs::write(shipped.join(&tampered), b"tampered weights").unwrap();
+
+        let lfs_file = |oid: &str, size| LfsFile {
+            path: format!("{}.bin", oid),
+            oid: Some(oid.to_string()),
+            size,
+            materialized: false,
+        };
+        let lfs_files = [lfs_file(&oid, content.len() as u64), lfs_file(&tampered, 13)];
+
+        let installed = restore_lfs_objects(repo, repo, &lfs_files).unwrap();
+        assert_eq!(installed, 1);
+        let objects_dir = lfs_objects_dir(repo).unwrap();
+        assert_eq!(fs::read(object_path(&objects_dir, &oid)).unwrap(), content);
+        assert!(!object_path(&objects_dir, &tampered).exists());
+
+        // The right content is still refused when its pointer disagrees on the size
+        fs::remove_file(object_path(&objects_dir, &oid)).unwrap();
+        let installed = restore_lfs_objects(repo, repo, &[lfs_file(&oid, 1)]).unwrap();
+        assert_eq!(installed, 0);
+        assert!(!object_path(&objects_dir, &oid).exists());
+    }
+}
@@ -0,0 +1,330 @@
+use git2::{
+    Branch, BranchType, ErrorCode, IndexAddOption, IndexEntry, IndexTime, Oid, Repository, ResetType,
+    Signature, StatusOptions,
+};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use tracing::debug;
+
+/// Failures of git operations, in-process or through the `git` binary
+#[derive(Debug, Error)]
+pub enum GitError {
+    #[error("{0} is not a git repository")]
+    NotARepository(PathBuf),
+
+    #[error("{0} is already a git repository")]
+    AlreadyARepository(PathBuf),
+
+    #[error("repository has no commits")]
+    NoCommits,
+
+    #[error("'{0}' is not a valid branch name")]
+    InvalidBranchName(String),
+
+    #[error("the git binary is required for {operation} but could not be run: {source}")]
+    BinaryUnavailable {
+        operation: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("git {command} failed: {stderr}")]
+    CommandFailed { command: String, stderr: String },
+
+    #[error(transparent)]
+    Libgit2(#[from] git2::Error),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+pub type GitResult<T> = std::result::Result<T, GitError>;
+
+/// State of a repository as seen from a workspace
+#[derive(Debug, Clone, Default)]
+pub struct GitState {
+    /// Checked-out branch, `None` when HEAD is detached
+    pub branch: Option<String>,
+    pub head: Option<String>,
+    pub has_uncommitted_changes: bool,
+    /// `(name, url)` of every remote
+    pub remotes: Vec<(String, Option<String>)>,
+    pub stash_count: usize,
+}
+
+/// A repository opened in-process, so no `git` binary is needed
+pub struct GitRepo {
+    repo: Repository,
+}
+
+impl GitRepo {
+    /// Open the repository containing `path`, if any
+    ///
+    /// Handles `.git` files of linked worktrees and submodules.
+    pub fn discover(path: &Path) -> GitResult<Option<Self>> {
+        match Repository::discover(path) {
+            Ok(repo) => Ok(Some(Self { repo })),
+            Err(e) if e.code() == ErrorCode::NotFound => {
+                debug!("{} is not inside a git repository", path.display());
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Open the repository whose work tree is exactly `path`
+    pub fn open_root(path: &Path) -> GitResult<Self> {
+        let repo = Self::discover(path)?.ok_or_else(|| GitError::NotARepository(path.to_path_buf()))?;
+        let is_root = match (repo.workdir(), path.canonicalize()) {
+            (Some(workdir), Ok(path)) => workdir.canonicalize().is_ok_and(|w| w == path),
+            _ => false,
+        };
+        if !is_root {
+            return Err(GitError::NotARepository(path.to_path_buf()));
+        }
+        Ok(repo)
+    }
+
+    /// Create a repository at `path` with HEAD on `branch`
+    pub fn init(path: &Path, branch: &str) -> GitResult<Self> {
+        if Self::open_root(path).is_ok() {
+            return Err(GitError::AlreadyARepository(path.to_path_buf()));
+        }
+        validate_branch_name(branch)?;
+
+        let repo = Repository::init(path)?;
+        repo.set_head(&format!("refs/heads/{}", branch))?;
+        Ok(Self { repo })
+    }
+
+    pub fn repository(&self) -> &Repository {
+        &self.repo
+    }
+
+    pub fn workdir(&self) -> Option<&Path> {
+        self.repo.workdir()
+    }
+
+    /// Directory shared by all worktrees of the repository
+    pub fn common_dir(&self) -> &Path {
+        self.repo.commondir()
+    }
+
+    pub fn is_worktree(&self) -> bool {
+        self.repo.is_worktree()
+    }
+
+    /// Branch HEAD points at, including an unborn branch; `None` when detached
+    pub fn branch(&self) -> GitResult<Option<String>> {
+        let head = self.repo.find_reference("HEAD")?;
+        Ok(head
+            .symbolic_target()
+            .map(|target| target.trim_start_matches("refs/heads/").to_string()))
+    }
+
+    pub fn head(&self) -> GitResult<Option<String>> {
+        match self.repo.head() {
+            Ok(head) => Ok(head.target().map(|oid| oid.to_string())),
+            Err(e) if matches!(e.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Whether the object database has commit `sha`
+    pub fn has_commit(&self, sha: &str) -> bool {
+        Oid::from_str(sha).is_ok_and(|oid| self.repo.find_commit(oid).is_ok())
+    }
+
+    /// Paths that differ from HEAD, untracked files included and ignored files left out
+    pub fn changed_paths(&self) -> GitResult<HashSet<String>> {
+        let mut options = StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .include_ignored(false);
+        let statuses = self.repo.statuses(Some(&mut options))?;
+        Ok(statuses.iter().filter_map(|entry| entry.path().map(str::to_string)).collect())
+    }
+
+    /// Commits HEAD is `(ahead, behind)` the upstream of `branch`; `None` without an upstream
+    pub fn ahead_behind(&self, branch: &str) -> GitResult<Option<(usize, usize)>> {
+        let Ok(upstream) = self.repo.find_branch(branch, BranchType::Local).and_then(|b| b.upstream()) else {
+            return Ok(None);
+        };
+        let (Some(local), Some(upstream)) = (self.repo.head()?.target(), upstream.get().target()) else {
+            return Ok(None);
+        };
+        Ok(Some(self.repo.graph_ahead_behind(local, upstream)?))
+    }
+
+    /// Number of commits on HEAD that no remote-tracking ref reaches
+    pub fn unpushed_commits(&self) -> GitResult<usize> {
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push_head()?;
+        revwalk.hide_glob("refs/remotes/*")?;
+        Ok(revwalk.collect::<Result<Vec<_>, _>>()?.len())
+    }
+
+    pub fn state(&mut self) -> GitResult<GitState> {
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).include_ignored(false);
+        let has_uncommitted_changes = !self.repo.statuses(Some(&mut options))?.is_empty();
+
+        let mut remotes = Vec::new();
+        for name in self.repo.remotes()?.iter().flatten() {
+            let url = self.repo.find_remote(name)?.url().map(str::to_string);
+            remotes.push((name.to_string(), url));
+        }
+
+        let mut stash_count = 0;
+        self.repo.stash_foreach(|_, _, _| {
+            stash_count += 1;
+            true
+        })?;
+
+        Ok(GitState {
+            branch: self.branch()?,
+            head: self.head()?,
+            has_uncommitted_changes,
+            remotes,
+            stash_count,
+        })
+    }
+
+    /// Commit the work tree and point `branch` at the commit, leaving HEAD and the index alone
+    ///
+    /// Ignored files and paths `skip` returns true for are left out, except the paths in `force`,
+    /// which are added even when ignored. Each `(path, source)` in `substitutes` is committed at
+    /// `path` with the content of `source`, e.g. a quarantined original. In a repository without commits, HEAD's unborn branch starts at the commit too
+    /// so `git diff <branch>` has a base.
+    pub fn commit_worktree(
+        &self,
+        branch: &str,
+        message: &str,
+        skip: impl Fn(&Path) -> bool,
+        force: &[String],
+        substitutes: &[(String, PathBuf)],
+    ) -> GitResult<String> {
+        validate_branch_name(branch)?;
+
+        let mut index = self.repo.index()?;
+        index.clear()?;
+        let mut filter = |path: &Path, _: &[u8]| -> i32 { if skip(path) { 1 } else { 0 } };
+        let filter: &mut git2::IndexMatchedPath = &mut filter;
+        index.add_all(["*"], IndexAddOption::DEFAULT, Some(filter))?;
+        for path in force {
+            // Adding by path bypasses the ignore rules
+            index.add_path(Path::new(path))?;
+        }
+        for (path, source) in substitutes {
+            let content = fs::read(source)?;
+            index.add_frombuffer(&file_entry(path, source, content.len())?, &content)?;
+        }
+        let tree = self.repo.find_tree(index.write_tree()?)?;
+        // Only the tree was wanted; drop the in-memory changes
+        index.read(true)?;
+
+        let parent = match self.repo.head() {
+            Ok(head) => Some(head.peel_to_commit()?),
+            Err(e) if matches!(e.code(), ErrorCode::UnbornBranch | ErrorCode::NotFound) => None,
+            Err(e) => return Err(e.into()),
+        };
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+
+        let signature = Signature::now("AgentBeam", "agentbeam@localhost")?;
+        let branch_ref = format!("refs/heads/{}", branch);
+        let commit = self.repo.commit(None, &signature, &signature, message, &tree, &parents)?;
+        self.repo.reference(&branch_ref, commit, true, "agentbeam: received beam")?;
+
+        if parent.is_none() {
+            let head = self.repo.find_reference("HEAD")?;
+            if let Some(target) = head.symbolic_target() {
+                self.repo.reference(target, commit, false, "agentbeam: received beam")?;
+                // Keep the index in step with the new HEAD; the work tree is untouched
+                let commit_object = self.repo.find_object(commit, None)?;
+                self.repo.reset(&commit_object, ResetType::Mixed, None)?;
+            }
+        }
+
+        Ok(commit.to_string())
+    }
+}
+
+/// Index entry for a regular file at `path`, executable if `source` is
+fn file_entry(path: &str, source: &Path, size: usize) -> GitResult<IndexEntry> {
+    #[cfg(unix)]
+    let executable = {
+        use std::os::unix::fs::PermissionsExt;
+        fs::metadata(source)?.permissions().mode() & 0o111 != 0
+    };
+    #[cfg(not(unix))]
+    let executable = false;
+
+    Ok(IndexEntry {
+        ctime: IndexTime::new(0, 0),
+        mtime: IndexTime::new(0, 0),
+        dev: 0,
+        ino: 0,
+        mode: if executable { 0o100755 } else { 0o100644 },
+        uid: 0,
+        gid: 0,
+        file_size: size as u32,
+        id: Oid::zero(),
+        flags: 0,
+        flags_extended: 0,
+        path: path.as_bytes().to_vec(),
+    })
+}
+
+pub fn validate_branch_name(branch: &str) -> GitResult<()> {
+    if Branch::name_is_valid(branch)? {
+        Ok(())
+    } else {
+        Err(GitError::InvalidBranchName(branch.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_init_state_and_commit_without_git_binary() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path();
+        fs::create_dir_all(path.join(".agentbeam")).unwrap();
+        fs::write(path.join("lib.rs"), "fn main() {}\n").unwrap();
+        fs::write(path.join(".agentbeam/session.jsonl"), "{}\n").unwrap();
+
+        let mut repo = GitRepo::init(path, "feature").unwrap();
+        let state = repo.state().unwrap();
+        assert_eq!(state.branch.as_deref(), Some("feature"));
+        assert_eq!(state.head, None);
+        assert!(state.has_uncommitted_changes);
+
+        let commit = repo
+            .commit_worktree("beam/abc/feature", "Received beam", |p| p.starts_with(".agentbeam"), &[], &[])
+            .unwrap();
+        let state = repo.state().unwrap();
+        assert_eq!(state.branch.as_deref(), Some("feature"));
+        assert_eq!(state.head.as_deref(), Some(commit.as_str()));
+        assert!(repo.repository().find_reference("refs/heads/beam/abc/feature").is_ok());
+
+        let tree = repo.repository().head().unwrap().peel_to_tree().unwrap();
+        let names: Vec<_> = tree.iter().filter_map(|e| e.name().map(str::to_string)).collect();
+        assert_eq!(names, vec!["lib.rs".to_string()]);
+
+        assert!(matches!(
+            GitRepo::init(path, "main"),
+            Err(GitError::AlreadyARepository(_))
+        ));
+        assert!(matches!(
+            validate_branch_name("bad..name"),
+            Err(GitError::InvalidBranchName(_))
+        ));
+    }
+}
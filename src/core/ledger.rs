@@ -0,0 +1,274 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::core::manifest::{SignedManifest, MANIFEST_PATH};
+
+/// Append-only log of beams, one JSON record per line
+const LEDGER_FILE: &str = "history.jsonl";
+
+/// Manifests of logged beams, one `<collection>.json` each
+const MANIFESTS_DIR: &str = "manifests";
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Outcome {
+    /// A receiver verified the beam and confirmed it
+    Delivered,
+    /// The ticket expired before anyone received the beam
+    Expired,
+    /// The sender stopped sharing before anyone received the beam
+    Cancelled,
+    /// The beam was received and matched the sender's manifest
+    Received,
+    Failed,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = match self {
+            Direction::Sent => "sent",
+            Direction::Received => "received",
+        };
+        write!(f, "{}", direction)
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcome = match self {
+            Outcome::Delivered => "delivered",
+            Outcome::Expired => "expired",
+            Outcome::Cancelled => "cancelled",
+            Outcome::Received => "received",
+            Outcome::Failed => "failed",
+        };
+        write!(f, "{}", outcome)
+    }
+}
+
+/// One beam sent or received
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerRecord {
+    pub timestamp: u64,
+    pub direction: Direction,
+    /// Hex hash of the collection
+    pub collection: String,
+    pub workspace_name: String,
+    pub file_count: u64,
+    pub total_size: u64,
+    /// NodeId of the other side, once known
+    pub peer: Option<String>,
+    pub peer_name: Option<String>,
+    /// `<agent> <session id>` for every beamed session
+    #[serde(default)]
+    pub sessions: Vec<String>,
+    /// Changes made to Claude config before sharing
+    #[serde(default)]
+    pub redactions: Vec<String>,
+    /// Secret scanner findings that were warned about or excluded
+    #[serde(default)]
+    pub secret_findings: Vec<String>,
+    pub outcome: Outcome,
+    /// Error or other detail for the outcome
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+/// Criteria for `agentbeam history`; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub direction: Option<Direction>,
+    /// Part of the peer's NodeId or name
+    pub peer: Option<String>,
+    pub workspace: Option<String>,
+    pub outcome: Option<Outcome>,
+    /// Only records at or after this unix time
+    pub since: Option<u64>,
+}
+
+impl HistoryFilter {
+    pub fn matches(&self, record: &LedgerRecord) -> bool {
+        let peer_matches = |needle: &str| {
+            let needle = needle.to_lowercase();
+            [&record.peer, &record.peer_name]
+                .into_iter()
+                .flatten()
+                .any(|value| value.to_lowercase().contains(&needle))
+        };
+
+        self.direction.is_none_or(|direction| record.direction == direction)
+            && self.outcome.is_none_or(|outcome| record.outcome == outcome)
+            && self.since.is_none_or(|since| record.timestamp >= since)
+            && self.workspace.as_deref().is_none_or(|workspace| record.workspace_name == workspace)
+            && self.peer.as_deref().is_none_or(peer_matches)
+    }
+}
+
+/// The audit ledger in the user's data directory
+pub struct Ledger {
+    dir: PathBuf,
+}
+
+impl Ledger {
+    pub fn open() -> Result<Self> {
+        Ok(Self::at(data_dir()?))
+    }
+
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.dir.join(LEDGER_FILE)
+    }
+
+    /// Append `record`, keeping the beam's signed manifest next to the ledger
+    pub fn append(&self, record: &LedgerRecord, manifest: Option<&[u8]>) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        if let Some(manifest) = manifest {
+            let manifests = self.dir.join(MANIFESTS_DIR);
+            fs::create_dir_all(&manifests)?;
+            fs::write(manifests.join(format!("{}.json", record.collection)), manifest)?;
+        }
+
+        // A single write per record keeps lines whole when two beams finish at once
+        let line = serde_json::to_string(record)? + "\n";
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path())
+            .with_context(|| format!("Failed to open {}", self.path().display()))?
+            .write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// Every record, oldest first; malformed lines are skipped with a warning
+    pub fn records(&self) -> Result<Vec<LedgerRecord>> {
+        let path = self.path();
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        let mut records = Vec::new();
+        for (number, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(record) => records.push(record),
+                Err(err) => warn!("Skipping line {} of {}: {}", number + 1, path.display(), err),
+            }
+        }
+        Ok(records)
+    }
+
+    /// Records whose collection hash starts with `prefix`
+    pub fn find(&self, prefix: &str) -> Result<Vec<LedgerRecord>> {
+        let prefix = prefix.to_lowercase();
+        Ok(self
+            .records()?
+            .into_iter()
+            .filter(|record| record.collection.starts_with(&prefix))
+            .collect())
+    }
+
+    /// The stored manifest of a collection, if it was kept
+    pub fn manifest(&self, collection: &str) -> Result<Option<SignedManifest>> {
+        let path = self.dir.join(MANIFESTS_DIR).join(format!("{}.json", collection));
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)?;
+        let signed = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        Ok(Some(signed))
+    }
+}
+
+/// AgentBeam's directory under the user's data dir, holding the ledger and delivery receipts
+pub fn data_dir() -> Result<PathBuf> {
+    let data_dir = dirs::data_dir().context("Failed to get data directory")?;
+    Ok(data_dir.join("agentbeam"))
+}
+
+/// The manifest a received directory came with, to keep in the ledger
+pub fn read_manifest(dir: &Path) -> Option<Vec<u8>> {
+    fs::read(dir.join(MANIFEST_PATH)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(direction: Direction, collection: &str, peer_name: &str, timestamp: u64) -> LedgerRecord {
+        LedgerRecord {
+            timestamp,
+            direction,
+            collection: collection.to_string(),
+            workspace_name: "webapp".to_string(),
+            file_count: 3,
+            total_size: 1024,
+            peer: Some("b7a3f0c2".to_string()),
+            peer_name: Some(peer_name.to_string()),
+            sessions: vec!["Claude Code 0f6c7b1e".to_string()],
+            redactions: vec![".mcp.json: redacted mcpServers.github.env (GITHUB_TOKEN)".to_string()],
+            secret_findings: Vec::new(),
+            outcome: if direction == Direction::Sent { Outcome::Delivered } else { Outcome::Received },
+            detail: None,
+        }
+    }
+
+    #[test]
+    fn test_ledger_appends_filters_and_keeps_manifests() {
+        let temp_dir = TempDir::new().unwrap();
+        let ledger = Ledger::at(temp_dir.path());
+        assert!(ledger.records().unwrap().is_empty());
+
+        ledger.append(&record(Direction::Sent, "aa11", "Alice", 100), Some(&b"{\"signature\":\"00\",\"manifest\":{}}"[..])).unwrap();
+        ledger.append(&record(Direction::Received, "bb22", "Bob", 200), None).unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(ledger.path())
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+
+        let records = ledger.records().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0], record(Direction::Sent, "aa11", "Alice", 100));
+
+        let filter = HistoryFilter {
+            peer: Some("bob".to_string()),
+            ..Default::default()
+        };
+        let matched: Vec<_> = records.iter().filter(|r| filter.matches(r)).collect();
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].collection, "bb22");
+
+        let filter = HistoryFilter {
+            direction: Some(Direction::Sent),
+            since: Some(150),
+            ..Default::default()
+        };
+        assert!(!records.iter().any(|r| filter.matches(r)));
+
+        assert_eq!(ledger.find("AA").unwrap().len(), 1);
+        assert_eq!(ledger.manifest("aa11").unwrap().unwrap().signature, "00");
+        assert!(ledger.manifest("bb22").unwrap().is_none());
+    }
+}
@@ -0,0 +1,317 @@
+use anyhow::{Context, Result};
+use iroh_base::{NodeId, SecretKey, Signature};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::path::{Component, Path};
+use std::str::FromStr;
+use tracing::debug;
+
+use crate::core::git_layout::LFS_PACKAGE_DIR;
+use crate::core::git_transfer::GIT_BUNDLE_PATH;
+use crate::core::quarantine::QUARANTINE_SUFFIX;
+
+/// Collection entry holding the signed manifest
+pub const MANIFEST_PATH: &str = ".agentbeam-manifest.json";
+
+pub const METADATA_PATH: &str = ".agentbeam-metadata.json";
+
+/// What an entry of the beam is for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FileRole {
+    Workspace,
+    /// Agent session files and files derived from them
+    Session,
+    /// Git bundles, thin-beam patches and LFS objects, consumed on receive
+    Git,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub size: u64,
+    /// Hex BLAKE3 hash of the content
+    pub hash: String,
+    /// Unix permission bits, without setuid, setgid and sticky
+    pub mode: u32,
+    pub role: FileRole,
+}
+
+/// Every file of a beam, as the sender imported it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeamManifest {
+    /// NodeId of the sender, whose key signs the manifest
+    pub sender: String,
+    pub created_at: u64,
+    pub total_size: u64,
+    /// Hex BLAKE3 hash of `.agentbeam-metadata.json`
+    pub metadata_hash: String,
+    pub entries: Vec<ManifestEntry>,
+}
+
+/// A manifest with the sender's signature over its exact serialized bytes
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignedManifest {
+    pub signature: String,
+    pub manifest: Box<RawValue>,
+}
+
+impl BeamManifest {
+    pub fn sign(&self, secret_key: &SecretKey) -> Result<SignedManifest> {
+        let manifest = RawValue::from_string(serde_json::to_string(self)?)?;
+        let signature = secret_key.sign(manifest.get().as_bytes());
+        Ok(SignedManifest {
+            signature: hex::encode(signature.to_bytes()),
+            manifest,
+        })
+    }
+}
+
+impl SignedManifest {
+    /// Load the manifest of a received directory; `None` for beams from senders without manifests
+    pub fn load(dir: &Path) -> Result<Option<Self>> {
+        let path = dir.join(MANIFEST_PATH);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&path)?;
+        let signed = serde_json::from_str(&content).context("Failed to parse beam manifest")?;
+        Ok(Some(signed))
+    }
+
+    /// Check the signature against the sender named in the manifest
+    pub fn verify(&self) -> Result<(NodeId, BeamManifest)> {
+        let manifest: BeamManifest =
+            serde_json::from_str(self.manifest.get()).context("Failed to parse beam manifest")?;
+        let sender = NodeId::from_str(&manifest.sender).context("Manifest names an invalid sender")?;
+
+        let bytes: [u8; 64] = hex::decode(&self.signature)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .context("Manifest signature is malformed")?;
+        sender
+            .verify(self.manifest.get().as_bytes(), &Signature::from_bytes(&bytes))
+            .context("Manifest signature does not match the sender")?;
+
+        // Entry paths are joined onto the receiving directory
+        for entry in &manifest.entries {
+            check_relative_path(&entry.path).context("Manifest has an unsafe entry")?;
+        }
+
+        Ok((sender, manifest))
+    }
+}
+
+/// Reject absolute paths and paths with `..`, which would reach outside the directory they are joined onto
+pub fn check_relative_path(path: &str) -> Result<()> {
+    let is_relative = !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    if !is_relative {
+        anyhow::bail!("{:?} is not a relative path inside the beam", path);
+    }
+    Ok(())
+}
+
+/// How a received file compares to the manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileState {
+    Unchanged,
+    Modified,
+    Missing,
+    /// Neutralized on receive; the original content is intact under the quarantine suffix
+    Quarantined,
+    /// A git entry that was unpacked into the repository on receive
+    Consumed,
+}
+
+/// Result of checking a received directory against its manifest
+#[derive(Debug)]
+pub struct VerifyReport {
+    pub sender: NodeId,
+    pub total_size: u64,
+    pub metadata_intact: bool,
+    pub files: Vec<(ManifestEntry, FileState)>,
+}
+
+impl VerifyReport {
+    pub fn with_state(&self, state: FileState) -> impl Iterator<Item = &ManifestEntry> {
+        self.files.iter().filter(move |(_, s)| *s == state).map(|(entry, _)| entry)
+    }
+
+    /// Every file is as the sender beamed it
+    pub fn is_intact(&self) -> bool {
+        self.metadata_intact
+            && self
+                .files
+                .iter()
+                .all(|(_, state)| !matches!(state, FileState::Modified | FileState::Missing))
+    }
+}
+
+/// Check the manifest signature and every file of a received directory
+///
+/// Returns `None` when the directory has no manifest.
+pub fn verify_dir(dir: &Path) -> Result<Option<VerifyReport>> {
+    let Some(signed) = SignedManifest::load(dir)? else {
+        return Ok(None);
+    };
+    let (sender, manifest) = signed.verify()?;
+
+    let metadata_intact = hash_file(&dir.join(METADATA_PATH)).ok().as_ref() == Some(&manifest.metadata_hash);
+
+    let mut files = Vec::with_capacity(manifest.entries.len());
+    for entry in manifest.entries {
+        let state = file_state(dir, &entry);
+        debug!("{}: {:?}", entry.path, state);
+        files.push((entry, state));
+    }
+
+    Ok(Some(VerifyReport {
+        sender,
+        total_size: manifest.total_size,
+        metadata_intact,
+        files,
+    }))
+}
+
+fn file_state(dir: &Path, entry: &ManifestEntry) -> FileState {
+    let path = dir.join(&entry.path);
+    if matches_entry(&path, entry) {
+        return FileState::Unchanged;
+    }
+
+    let quarantined = dir.join(format!("{}{}", entry.path, QUARANTINE_SUFFIX));
+    if matches_entry(&quarantined, entry) {
+        return FileState::Quarantined;
+    }
+
+    if path.exists() {
+        FileState::Modified
+    } else if entry.role == FileRole::Git {
+        FileState::Consumed
+    } else {
+        FileState::Missing
+    }
+}
+
+fn matches_entry(path: &Path, entry: &ManifestEntry) -> bool {
+    // Compare sizes first so most changed files are caught without hashing
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() && metadata.len() == entry.size => {
+            hash_file(path).is_ok_and(|hash| hash == entry.hash)
+        }
+        _ => false,
+    }
+}
+
+/// Hex BLAKE3 hash of a file, streamed
+pub fn hash_file(path: &Path) -> Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update_reader(File::open(path)?)?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Role of a collection entry, given the files of the beamed agent sessions
+pub fn role_of(path: &str, session_files: &HashSet<String>) -> FileRole {
+    if path == GIT_BUNDLE_PATH || path.starts_with(".agentbeam/thin/") || path.starts_with(LFS_PACKAGE_DIR) {
+        FileRole::Git
+    } else if path.starts_with(".agentbeam/") || session_files.contains(path) {
+        FileRole::Session
+    } else {
+        FileRole::Workspace
+    }
+}
+
+#[cfg(unix)]
+pub fn file_mode(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o777
+}
+
+#[cfg(not(unix))]
+pub fn file_mode(metadata: &fs::Metadata) -> u32 {
+    if metadata.permissions().readonly() { 0o444 } else { 0o644 }
+}
+
+/// Give received files the permission bits they had on the sender's machine
+#[cfg(unix)]
+pub fn apply_modes<'a>(dir: &Path, entries: impl IntoIterator<Item = &'a ManifestEntry>) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    for entry in entries {
+        let path = dir.join(&entry.path);
+        if path.is_file() {
+            fs::set_permissions(&path, fs::Permissions::from_mode(entry.mode))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn apply_modes<'a>(_dir: &Path, _entries: impl IntoIterator<Item = &'a ManifestEntry>) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_signed_manifest_detects_tampering_and_local_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::write(dir.join("lib.rs"), "fn main() {}\n").unwrap();
+        fs::write(dir.join(".envrc.quarantined"), "export A=1\n").unwrap();
+        fs::write(dir.join(METADATA_PATH), "{}").unwrap();
+
+        let entry = |path: &str, content: &str, role| ManifestEntry {
+            path: path.to_string(),
+            size: content.len() as u64,
+            hash: blake3::hash(content.as_bytes()).to_hex().to_string(),
+            mode: 0o644,
+            role,
+        };
+        let secret_key = SecretKey::from_bytes(&rand::random::<[u8; 32]>());
+        let manifest = BeamManifest {
+            sender: secret_key.public().to_string(),
+            created_at: 1_700_000_000,
+            total_size: 37,
+            metadata_hash: blake3::hash(b"{}").to_hex().to_string(),
+            entries: vec![
+                entry("lib.rs", "fn main() {}\n", FileRole::Workspace),
+                entry(".envrc", "export A=1\n", FileRole::Workspace),
+                entry(".agentbeam/repo.bundle", "bundle", FileRole::Git),
+            ],
+        };
+        let signed = manifest.sign(&secret_key).unwrap();
+        fs::write(dir.join(MANIFEST_PATH), serde_json::to_string_pretty(&signed).unwrap()).unwrap();
+
+        let report = verify_dir(dir).unwrap().unwrap();
+        assert_eq!(report.sender, secret_key.public());
+        assert!(report.is_intact());
+        let states: Vec<_> = report.files.iter().map(|(_, state)| state.clone()).collect();
+        assert_eq!(states, vec![FileState::Unchanged, FileState::Quarantined, FileState::Consumed]);
+
+        fs::write(dir.join("lib.rs"), "fn main() { todo!() }\n").unwrap();
+        let report = verify_dir(dir).unwrap().unwrap();
+        assert!(!report.is_intact());
+        assert_eq!(report.with_state(FileState::Modified).count(), 1);
+
+        let tampered = serde_json::to_string(&signed).unwrap().replace("1700000000", "1700000001");
+        fs::write(dir.join(MANIFEST_PATH), tampered).unwrap();
+        assert!(verify_dir(dir).is_err());
+
+        // A validly signed entry may still not leave the directory
+        for path in ["../../outside", "/etc/passwd", "a/../../b"] {
+            let escaping = BeamManifest {
+                entries: vec![entry(path, "x", FileRole::Workspace)],
+                ..manifest.clone()
+            };
+            assert!(escaping.sign(&secret_key).unwrap().verify().is_err(), "{}", path);
+        }
+    }
+}
@@ -1,11 +1,28 @@
 pub mod agent_beam;
+pub mod agents;
+pub mod claude_config;
 pub mod claude_session;
 pub mod cleanup;
 pub mod config;
+pub mod control;
+pub mod encryption;
 pub mod file_collector;
+pub mod git_layout;
+pub mod git_repo;
+pub mod git_transfer;
+pub mod ledger;
+pub mod manifest;
 pub mod provider_monitor;
+pub mod quarantine;
+pub mod receipt;
 pub mod receiver;
+pub mod schema;
+pub mod secret_scanner;
+pub mod ticket;
 
 pub use agent_beam::AgentBeam;
-pub use claude_session::{ClaudeContext, ClaudeSessionInfo, GitContext};
-pub use config::{BeamConfig, ConnectionMode, BeamContent, BeamMetadata};
\ No newline at end of file
+pub use agents::{AgentAdapter, AgentKind, AgentSessionInfo};
+pub use claude_session::{ClaudeContext, ClaudeSessionInfo, GitContext, SessionCutoff, SessionStats};
+pub use config::{BeamConfig, ClaudeConfigPolicy, ConnectionMode, BeamContent, BeamMetadata, SecretPolicy};
+pub use git_repo::{GitError, GitRepo};
+pub use secret_scanner::{SecretFinding, SecretScanner};
\ No newline at end of file
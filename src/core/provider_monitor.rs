@@ -1,29 +1,69 @@
 use anyhow::Result;
 use colored::Colorize;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use iroh::{Endpoint, endpoint::ConnectionType, Watcher};
+use iroh::{Endpoint, NodeId, endpoint::ConnectionType, Watcher};
 use iroh_blobs::provider::Event;
 use std::collections::{HashMap, HashSet};
 use tokio::sync::mpsc;
 use tracing::{debug, trace};
 
+use crate::core::control::ControlEvent;
+use crate::core::receipt::SignedReceipt;
+
+/// A receiver that verified the beam and confirmed it over the control protocol
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    pub receiver: NodeId,
+    pub display_name: Option<String>,
+    /// The receiver's signed delivery receipt, if it sent one
+    pub receipt: Option<SignedReceipt>,
+}
+
 pub struct ProviderMonitor<'a> {
     receiver: mpsc::Receiver<Event>,
+    control: mpsc::Receiver<ControlEvent>,
     mp: Option<&'a MultiProgress>,
     endpoint: &'a Endpoint,
+    /// Receivers admitted by the control handshake, with their display names
+    accepted: HashMap<NodeId, Option<String>>,
 }
 
 impl<'a> ProviderMonitor<'a> {
-    pub fn new(receiver: mpsc::Receiver<Event>, mp: Option<&'a MultiProgress>, endpoint: &'a Endpoint) -> Self {
-        Self { receiver, mp, endpoint }
+    pub fn new(
+        receiver: mpsc::Receiver<Event>,
+        control: mpsc::Receiver<ControlEvent>,
+        mp: Option<&'a MultiProgress>,
+        endpoint: &'a Endpoint,
+    ) -> Self {
+        Self {
+            receiver,
+            control,
+            mp,
+            endpoint,
+            accepted: HashMap::new(),
+        }
     }
 
-    pub async fn monitor_until_complete(&mut self) -> Result<()> {
+    /// Serve blobs until a receiver reports a verified beam over the control protocol
+    ///
+    /// Returns `None` if the provider stopped before any receiver confirmed the beam.
+    pub async fn monitor_until_complete(&mut self) -> Result<Option<Delivery>> {
         let mut active_transfers: HashSet<u64> = HashSet::new();
         let mut transfer_bars: HashMap<u64, ProgressBar> = HashMap::new();
-        let mut connected = false;
 
-        while let Some(event) = self.receiver.recv().await {
+        loop {
+            let event = tokio::select! {
+                Some(event) = self.control.recv() => {
+                    if let Some(delivery) = self.handle_control(event) {
+                        return Ok(Some(delivery));
+                    }
+                    continue;
+                }
+                event = self.receiver.recv() => match event {
+                    Some(event) => event,
+                    None => return Ok(None),
+                },
+            };
             trace!("Provider event: {:?}", event);
             
             match event {
@@ -32,7 +72,21 @@ impl<'a> ProviderMonitor<'a> {
                     node_id,
                     permitted,
                 } => {
-                    println!("{} Peer {} connected", "✓".green(), node_id);
+                    // The handshake may have been accepted just before this connection arrived
+                    while let Ok(event) = self.control.try_recv() {
+                        if let Some(delivery) = self.handle_control(event) {
+                            return Ok(Some(delivery));
+                        }
+                    }
+                    let Some(display_name) = self.accepted.get(&node_id) else {
+                        println!("{} Refused peer {} without an agentbeam/1 handshake", "⚠".yellow(), node_id);
+                        permitted.send(false).await.ok();
+                        continue;
+                    };
+                    match display_name {
+                        Some(name) => println!("{} Peer {} ({}) connected", "✓".green(), name, node_id),
+                        None => println!("{} Peer {} connected", "✓".green(), node_id),
+                    }
                     
                     // Get actual connection type from endpoint
                     let path = if let Some(mut conn_type_watcher) = self.endpoint.conn_type(node_id) {
@@ -54,7 +108,6 @@ impl<'a> ProviderMonitor<'a> {
                     );
                     
                     permitted.send(true).await.ok();
-                    connected = true;
                 }
                 
                 Event::GetRequestReceived { 
@@ -79,7 +132,7 @@ impl<'a> ProviderMonitor<'a> {
                     
                     active_transfers.insert(request_id);
                     
-                    if let Some(ref mp) = self.mp {
+                    if let Some(mp) = self.mp {
                         let pb = mp.add(ProgressBar::new(size));
                         pb.set_style(
                             ProgressStyle::default_bar()
@@ -113,7 +166,7 @@ impl<'a> ProviderMonitor<'a> {
                     }
                     
                     debug!("Transfer {} completed", request_id);
-                    // Don't exit here - wait for ConnectionClosed event
+                    // Don't exit here - wait for the receiver to verify and report completion
                 }
                 
                 Event::TransferAborted {
@@ -129,19 +182,48 @@ impl<'a> ProviderMonitor<'a> {
                     println!("{} Transfer {} aborted", "⚠".yellow(), request_id);
                 }
                 
-                Event::ConnectionClosed { .. } => {
-                    if connected {
-                        println!("{} Connection closed by receiver.", "✓".green());
-                        // Give a brief moment for cleanup
-                        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                        return Ok(());
-                    }
+                Event::ConnectionClosed { connection_id } => {
+                    debug!("Blob connection {} closed", connection_id);
                 }
                 
                 _ => {}
             }
         }
-        
-        Ok(())
+    }
+
+    /// Track a control protocol event; returns the delivery once a receiver confirmed the beam
+    fn handle_control(&mut self, event: ControlEvent) -> Option<Delivery> {
+        debug!("Control event: {:?}", event);
+        match event {
+            ControlEvent::Accepted { node_id, display_name } => {
+                tracing::info!(event = "handshake_accepted", node_id = %node_id, role = "sender");
+                self.accepted.insert(node_id, display_name);
+                None
+            }
+            ControlEvent::Declined { node_id, reason } => {
+                println!("{} Declined peer {}: {}", "⚠".yellow(), node_id, reason);
+                None
+            }
+            ControlEvent::Completed { node_id, root_hash, file_count, receipt } => {
+                println!(
+                    "{} Receiver {} verified {} files (root {})",
+                    "✓".green(),
+                    node_id,
+                    file_count,
+                    root_hash.to_hex().chars().take(8).collect::<String>()
+                );
+                Some(Delivery {
+                    receiver: node_id,
+                    display_name: self.accepted.get(&node_id).cloned().flatten(),
+                    receipt,
+                })
+            }
+            ControlEvent::Failed { node_id, message } => {
+                self.accepted.remove(&node_id);
+                println!("{} Receiver {} did not complete: {}", "⚠".yellow(), node_id, message);
+                println!("   Still sharing; the receiver can try again");
+                None
+            }
+        }
     }
 }
\ No newline at end of file
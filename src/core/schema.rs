@@ -0,0 +1,192 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+use std::fmt;
+use std::str::FromStr;
+use tracing::debug;
+
+use crate::core::agents::claude::CLAUDE_SESSION_PATH;
+use crate::core::config::BeamMetadata;
+
+/// Version of the `.agentbeam-metadata.json` layout
+///
+/// Minor versions only add fields, which older readers ignore. A new major version changes the
+/// meaning of existing fields, so older readers must refuse the beam.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SchemaVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+/// Metadata written before `schema_version` existed
+pub const LEGACY_SCHEMA: SchemaVersion = SchemaVersion { major: 1, minor: 0 };
+
+/// The version this build writes
+///
+/// 1.1 adds `schema_version` itself and always lists sessions in `agent_sessions`.
+pub const CURRENT_SCHEMA: SchemaVersion = SchemaVersion { major: 1, minor: 1 };
+
+impl Default for SchemaVersion {
+    fn default() -> Self {
+        LEGACY_SCHEMA
+    }
+}
+
+impl fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl FromStr for SchemaVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (major, minor) = s
+            .split_once('.')
+            .with_context(|| format!("Schema version '{}' is not MAJOR.MINOR", s))?;
+        Ok(Self {
+            major: major.parse().with_context(|| format!("Invalid schema major version '{}'", major))?,
+            minor: minor.parse().with_context(|| format!("Invalid schema minor version '{}'", minor))?,
+        })
+    }
+}
+
+impl Serialize for SchemaVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for SchemaVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Parse metadata of any compatible version, upgrading older minor versions to the current one
+///
+/// Fields added by newer minor versions are kept in [`BeamMetadata::extra`].
+pub fn parse_metadata(content: &str) -> Result<BeamMetadata> {
+    let mut value: Value = serde_json::from_str(content).context("Beam metadata is not valid JSON")?;
+
+    let version = match value.get("schema_version") {
+        None => LEGACY_SCHEMA,
+        Some(version) => version
+            .as_str()
+            .context("Beam metadata has a malformed schema_version")?
+            .parse()?,
+    };
+
+    if version.major > CURRENT_SCHEMA.major {
+        anyhow::bail!(
+            "This beam uses metadata schema {}, but AgentBeam {} only reads schema {}.x\n\
+             Upgrade AgentBeam (cargo install agentbeam) and receive again",
+            version,
+            env!("CARGO_PKG_VERSION"),
+            CURRENT_SCHEMA.major
+        );
+    }
+    if version.major < CURRENT_SCHEMA.major {
+        anyhow::bail!(
+            "This beam uses metadata schema {}, which AgentBeam {} no longer reads\n\
+             Ask the sender to upgrade AgentBeam and beam again",
+            version,
+            env!("CARGO_PKG_VERSION")
+        );
+    }
+
+    if version < CURRENT_SCHEMA {
+        upgrade(&mut value, version)?;
+    } else if version > CURRENT_SCHEMA {
+        debug!("Reading metadata schema {} as {}; newer fields are kept as is", version, CURRENT_SCHEMA);
+    }
+
+    serde_json::from_value(value).context("Failed to parse beam metadata")
+}
+
+/// Rewrite `value` step by step from `version` to the current schema
+fn upgrade(value: &mut Value, mut version: SchemaVersion) -> Result<()> {
+    let object = value.as_object_mut().context("Beam metadata is not a JSON object")?;
+
+    while version < CURRENT_SCHEMA {
+        match (version.major, version.minor) {
+            (1, 0) => {
+                // Legacy beams carry only the Claude session, outside `agent_sessions`
+                let has_sessions = object
+                    .get("agent_sessions")
+                    .and_then(Value::as_array)
+                    .is_some_and(|sessions| !sessions.is_empty());
+                if let Some(claude) = object.get("claude_session").filter(|c| !c.is_null()).cloned() && !has_sessions {
+                    object.insert(
+                        "agent_sessions".to_string(),
+                        json!([{
+                            "agent": "claude-code",
+                            "session_id": claude["original_session_id"],
+                            "entry_count": claude["entry_count"],
+                            "files": [CLAUDE_SESSION_PATH],
+                            "source_workspace": null,
+                        }]),
+                    );
+                }
+            }
+            _ => anyhow::bail!("No upgrade path from metadata schema {}", version),
+        }
+
+        version.minor += 1;
+        debug!("Upgraded beam metadata to schema {}", version);
+    }
+
+    object.insert("schema_version".to_string(), json!(CURRENT_SCHEMA.to_string()));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::agents::AgentKind;
+    use crate::test_utils::fixtures;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_legacy_metadata_is_upgraded() {
+        let metadata = parse_metadata(fixtures::METADATA_1_0).unwrap();
+
+        assert_eq!(metadata.schema_version, CURRENT_SCHEMA);
+        assert_eq!(metadata.workspace_name, "webapp");
+        assert_eq!(metadata.git_context.as_ref().unwrap().branch, "main");
+        assert_eq!(metadata.agent_sessions.len(), 1);
+        assert_eq!(metadata.agent_sessions[0].agent, AgentKind::ClaudeCode);
+        assert_eq!(metadata.agent_sessions[0].session_id, "0f6c7b1e-3d2a-4c8e-9b1f-2a7d5e6c8b90");
+        assert_eq!(metadata.agent_sessions[0].files, vec![CLAUDE_SESSION_PATH.to_string()]);
+        assert!(metadata.extra.is_empty());
+    }
+
+    #[test]
+    fn test_current_metadata_json_is_pinned() {
+        let fixture: Value = serde_json::from_str(fixtures::METADATA_1_1).unwrap();
+        let metadata = parse_metadata(fixtures::METADATA_1_1).unwrap();
+
+        // Any change to the written JSON needs a new minor version and fixture
+        assert_eq!(serde_json::to_value(&metadata).unwrap(), fixture);
+        assert_eq!(metadata.schema_version, CURRENT_SCHEMA);
+    }
+
+    #[test]
+    fn test_newer_minor_keeps_unknown_fields_and_newer_major_is_refused() {
+        let mut newer: Value = serde_json::from_str(fixtures::METADATA_1_1).unwrap();
+        newer["schema_version"] = json!("1.7");
+        newer["teleport"] = json!({"enabled": true});
+
+        let metadata = parse_metadata(&newer.to_string()).unwrap();
+        assert_eq!(metadata.schema_version, SchemaVersion { major: 1, minor: 7 });
+        assert_eq!(metadata.unknown_fields(), vec!["teleport"]);
+        assert_eq!(serde_json::to_value(&metadata).unwrap()["teleport"]["enabled"], true);
+
+        newer["schema_version"] = json!("2.0");
+        let error = parse_metadata(&newer.to_string()).unwrap_err().to_string();
+        assert!(error.contains("schema 2.0"));
+        assert!(error.contains("Upgrade AgentBeam"));
+    }
+}
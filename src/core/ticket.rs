@@ -0,0 +1,278 @@
+use anyhow::{Context, Result};
+use iroh_base::ticket::{self, Ticket};
+use iroh_base::{SecretKey, Signature};
+use iroh_blobs::{ticket::BlobTicket, Hash};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use crate::core::config::BeamMetadata;
+
+/// How long a ticket stays valid unless the sender picks another duration
+pub const DEFAULT_TICKET_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// What a beam contains, readable from the ticket before connecting
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TicketSummary {
+    pub workspace_name: String,
+    pub total_size: u64,
+    pub file_count: u64,
+    /// Display names of the agents whose sessions are included
+    pub agents: Vec<String>,
+    pub created_at: u64,
+    /// Unix time after which receivers refuse the ticket; `None` never expires
+    pub expires_at: Option<u64>,
+    pub sender_name: Option<String>,
+}
+
+impl TicketSummary {
+    pub fn new(metadata: &BeamMetadata, ttl: Option<Duration>, sender_name: Option<String>) -> Result<Self> {
+        let expires_at = match ttl {
+            Some(ttl) => Some(
+                metadata
+                    .created_at
+                    .checked_add(ttl.as_secs())
+                    .context("Ticket lifetime is too long")?,
+            ),
+            None => None,
+        };
+        Ok(Self {
+            workspace_name: metadata.workspace_name.clone(),
+            total_size: metadata.total_size,
+            file_count: metadata.file_count as u64,
+            agents: metadata.agent_sessions.iter().map(|s| s.agent.to_string()).collect(),
+            created_at: metadata.created_at,
+            expires_at,
+            sender_name,
+        })
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|expires_at| unix_now() >= expires_at)
+    }
+
+    /// Time left before expiry, `None` if the ticket never expires
+    pub fn remaining(&self) -> Option<Duration> {
+        self.expires_at
+            .map(|expires_at| Duration::from_secs(expires_at.saturating_sub(unix_now())))
+    }
+}
+
+/// A blob ticket with a summary of the beam, signed by the sending node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentBeamTicket {
+    blob: BlobTicket,
+    summary: TicketSummary,
+    signature: Signature,
+}
+
+/// Wire format for [`AgentBeamTicket`], a single variant enum so postcard writes a discriminator
+#[derive(Serialize, Deserialize)]
+enum TicketWireFormat {
+    Variant0 {
+        blob: BlobTicket,
+        summary: TicketSummary,
+        signature: Signature,
+    },
+}
+
+impl AgentBeamTicket {
+    /// Sign `summary` for the collection in `blob`; `secret_key` must belong to the ticket's node
+    pub fn new(blob: BlobTicket, summary: TicketSummary, secret_key: &SecretKey) -> Self {
+        let signature = secret_key.sign(&signed_bytes(blob.hash(), &summary));
+        Self { blob, summary, signature }
+    }
+
+    pub fn blob(&self) -> &BlobTicket {
+        &self.blob
+    }
+
+    /// Check that the sending node signed the summary for this collection
+    pub fn verify(&self) -> Result<&TicketSummary> {
+        self.blob
+            .node_addr()
+            .node_id
+            .verify(&signed_bytes(self.blob.hash(), &self.summary), &self.signature)
+            .context("Ticket summary is not signed by the sending node")?;
+        Ok(&self.summary)
+    }
+}
+
+/// The summary is bound to the collection hash so it cannot be moved onto another ticket
+fn signed_bytes(hash: Hash, summary: &TicketSummary) -> Vec<u8> {
+    postcard::to_stdvec(&(hash, summary)).expect("postcard serialization failed")
+}
+
+impl Ticket for AgentBeamTicket {
+    const KIND: &'static str = "agentbeam";
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let data = TicketWireFormat::Variant0 {
+            blob: self.blob.clone(),
+            summary: self.summary.clone(),
+            signature: self.signature,
+        };
+        postcard::to_stdvec(&data).expect("postcard serialization failed")
+    }
+
+    fn from_bytes(bytes: &[u8]) -> std::result::Result<Self, ticket::ParseError> {
+        let res: TicketWireFormat = postcard::from_bytes(bytes)?;
+        let TicketWireFormat::Variant0 { blob, summary, signature } = res;
+        Ok(Self { blob, summary, signature })
+    }
+}
+
+impl FromStr for AgentBeamTicket {
+    type Err = ticket::ParseError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ticket::deserialize(s)
+    }
+}
+
+impl fmt::Display for AgentBeamTicket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Ticket::serialize(self))
+    }
+}
+
+/// A ticket as given on the command line: an AgentBeam ticket or a plain blob ticket from older senders
+#[derive(Debug, Clone)]
+pub enum TicketInput {
+    AgentBeam(AgentBeamTicket),
+    Plain(BlobTicket),
+}
+
+impl TicketInput {
+    pub fn blob(&self) -> &BlobTicket {
+        match self {
+            TicketInput::AgentBeam(ticket) => ticket.blob(),
+            TicketInput::Plain(blob) => blob,
+        }
+    }
+
+    /// Verify the summary, if any, and refuse expired tickets
+    pub fn check(&self) -> Result<Option<&TicketSummary>> {
+        let TicketInput::AgentBeam(ticket) = self else {
+            return Ok(None);
+        };
+        let summary = ticket.verify()?;
+        if summary.is_expired() {
+            anyhow::bail!(
+                "This ticket expired at {}\nAsk the sender to beam again",
+                format_time(summary.expires_at.unwrap_or_default())
+            );
+        }
+        Ok(Some(summary))
+    }
+}
+
+impl FromStr for TicketInput {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.starts_with(AgentBeamTicket::KIND) {
+            let ticket = AgentBeamTicket::from_str(s).context("Invalid AgentBeam ticket")?;
+            return Ok(TicketInput::AgentBeam(ticket));
+        }
+        let blob = BlobTicket::from_str(s).context("Invalid ticket format")?;
+        Ok(TicketInput::Plain(blob))
+    }
+}
+
+/// Parse a duration such as `90s`, `30m`, `12h` or `7d`; the unit is required
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (value, unit) = s.split_at(split);
+    let value: u64 = value
+        .parse()
+        .with_context(|| format!("Invalid duration '{}', expected e.g. 30m, 12h or 7d", s))?;
+    let unit_seconds: u64 = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "" => anyhow::bail!("Duration '{}' needs a unit: s, m, h or d", s),
+        _ => anyhow::bail!("Invalid duration unit '{}', expected s, m, h or d", unit),
+    };
+    let seconds = value
+        .checked_mul(unit_seconds)
+        .with_context(|| format!("Duration '{}' is too long", s))?;
+    if seconds == 0 {
+        anyhow::bail!("Duration must be greater than zero");
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Local time of a unix timestamp, for display
+pub fn format_time(timestamp: u64) -> String {
+    chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M %Z").to_string())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iroh::NodeAddr;
+    use iroh_blobs::BlobFormat;
+
+    #[test]
+    fn test_ticket_round_trip_verify_and_plain_fallback() {
+        let secret_key = SecretKey::from_bytes(&rand::random::<[u8; 32]>());
+        let blob = BlobTicket::new(
+            NodeAddr::new(secret_key.public()),
+            Hash::new(b"collection"),
+            BlobFormat::HashSeq,
+        );
+        let summary = TicketSummary {
+            workspace_name: "webapp".to_string(),
+            total_size: 1_234_567,
+            file_count: 42,
+            agents: vec!["Claude Code".to_string()],
+            created_at: unix_now(),
+            expires_at: Some(unix_now() + 3600),
+            sender_name: Some("alice".to_string()),
+        };
+
+        let ticket = AgentBeamTicket::new(blob.clone(), summary.clone(), &secret_key);
+        let encoded = ticket.to_string();
+        assert!(encoded.starts_with("agentbeam"));
+
+        let parsed: TicketInput = encoded.parse().unwrap();
+        assert_eq!(parsed.blob(), &blob);
+        assert_eq!(parsed.check().unwrap(), Some(&summary));
+
+        // A summary signed by another key is rejected
+        let other_key = SecretKey::from_bytes(&rand::random::<[u8; 32]>());
+        let forged = AgentBeamTicket::new(blob.clone(), summary.clone(), &other_key);
+        assert!(forged.verify().is_err());
+
+        let expired = TicketSummary {
+            expires_at: Some(summary.created_at - 1),
+            ..summary
+        };
+        let expired = TicketInput::AgentBeam(AgentBeamTicket::new(blob.clone(), expired, &secret_key));
+        assert!(expired.check().unwrap_err().to_string().contains("expired"));
+
+        let plain: TicketInput = blob.to_string().parse().unwrap();
+        assert!(matches!(plain, TicketInput::Plain(_)));
+        assert!(plain.check().unwrap().is_none());
+
+        assert_eq!(parse_duration("30m").unwrap(), Duration::from_secs(1800));
+        assert_eq!(parse_duration("7d").unwrap(), Duration::from_secs(7 * 86400));
+        assert!(parse_duration("0h").is_err());
+        assert!(parse_duration("2w").is_err());
+        assert!(parse_duration("12").is_err());
+        assert!(parse_duration("18446744073709551615d").is_err());
+    }
+}
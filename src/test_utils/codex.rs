@@ -0,0 +1,85 @@
+use anyhow::Result;
+use std::fs::{self, create_dir_all};
+use std::path::{Path, PathBuf};
+
+/// A Codex CLI rollout written under a fake `CODEX_HOME`
+pub struct CodexRolloutFixture {
+    pub session_id: String,
+    pub path: PathBuf,
+}
+
+impl CodexRolloutFixture {
+    /// Write a rollout for `cwd` dated `date` (`YYYY/MM/DD`) with `turns` user/assistant exchanges
+    pub fn create(codex_home: &Path, cwd: &Path, date: &str, session_id: &str, turns: usize) -> Result<Self> {
+        let day_dir = codex_home.join("sessions").join(date);
+        create_dir_all(&day_dir)?;
+
+        let timestamp = format!("{}T10:00:00", date.replace('/', "-"));
+        let path = day_dir.join(format!("rollout-{}-{}.jsonl", timestamp.replace(':', "-"), session_id));
+        let cwd = cwd.to_string_lossy();
+
+        let mut lines = vec![serde_json::json!({
+            "timestamp": format!("{}.000Z", timestamp),
+            "type": "session_meta",
+            "payload": {
+                "id": session_id,
+                "timestamp": format!("{}.000Z", timestamp),
+                "cwd": cwd,
+                "originator": "codex_cli_rs",
+                "cli_version": "0.40.0",
+                "instructions": null,
+            },
+        })];
+
+        for turn in 0..turns {
+            lines.push(serde_json::json!({
+                "timestamp": format!("{}.{:03}Z", timestamp, turn + 1),
+                "type": "turn_context",
+                "payload": {
+                    "cwd": cwd,
+                    "approval_policy": "on-request",
+                    "model": "gpt-5-codex",
+                },
+            }));
+            lines.push(serde_json::json!({
+                "timestamp": format!("{}.{:03}Z", timestamp, turn + 1),
+                "type": "response_item",
+                "payload": {
+                    "type": "message",
+                    "role": "user",
+                    "content": [{"type": "input_text", "text": format!("request {}", turn + 1)}],
+                },
+            }));
+            lines.push(serde_json::json!({
+                "timestamp": format!("{}.{:03}Z", timestamp, turn + 1),
+                "type": "response_item",
+                "payload": {
+                    "type": "function_call",
+                    "name": "shell",
+                    "arguments": serde_json::json!({
+                        "command": ["cargo", "test"],
+                        "workdir": cwd,
+                    }).to_string(),
+                    "call_id": format!("call_{}", turn + 1),
+                },
+            }));
+            lines.push(serde_json::json!({
+                "timestamp": format!("{}.{:03}Z", timestamp, turn + 1),
+                "type": "response_item",
+                "payload": {
+                    "type": "message",
+                    "role": "assistant",
+                    "content": [{"type": "output_text", "text": format!("done {}", turn + 1)}],
+                },
+            }));
+        }
+
+        let content: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        fs::write(&path, content.join("\n"))?;
+
+        Ok(Self {
+            session_id: session_id.to_string(),
+            path,
+        })
+    }
+}
@@ -173,10 +173,10 @@ impl DummyWorkspace {
     }
     
     fn setup_workspace(&self) -> Result<()> {
-        create_dir_all(&self.workspace_dir.join("src/utils"))?;
-        create_dir_all(&self.workspace_dir.join("tests"))?;
-        create_dir_all(&self.workspace_dir.join(".git"))?;
-        create_dir_all(&self.workspace_dir.join("target/debug/build"))?;
+        create_dir_all(self.workspace_dir.join("src/utils"))?;
+        create_dir_all(self.workspace_dir.join("tests"))?;
+        create_dir_all(self.workspace_dir.join(".git"))?;
+        create_dir_all(self.workspace_dir.join("target/debug/build"))?;
         
         fs::write(self.workspace_dir.join("src/main.rs"), SAMPLE_MAIN_RS)?;
         fs::write(self.workspace_dir.join("src/lib.rs"), SAMPLE_LIB_RS)?;
@@ -210,9 +210,9 @@ impl DummyWorkspace {
     
     fn create_dummy_node_modules(&self, file_count: usize) -> Result<()> {
         let node_modules = self.workspace_dir.join("node_modules");
-        create_dir_all(&node_modules.join("package1/lib"))?;
-        create_dir_all(&node_modules.join("package2/dist"))?;
-        create_dir_all(&node_modules.join("@scope/package3/src"))?;
+        create_dir_all(node_modules.join("package1/lib"))?;
+        create_dir_all(node_modules.join("package2/dist"))?;
+        create_dir_all(node_modules.join("@scope/package3/src"))?;
         
         for i in 0..file_count {
             let content = format!("// Dummy file {} for size testing\nmodule.exports = {{}};", i);
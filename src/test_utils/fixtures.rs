@@ -0,0 +1,6 @@
+//! Pinned `.agentbeam-metadata.json` of every released schema version
+
+/// Written before `schema_version` existed
+pub const METADATA_1_0: &str = include_str!("fixtures/metadata-1.0.json");
+
+pub const METADATA_1_1: &str = include_str!("fixtures/metadata-1.1.json");
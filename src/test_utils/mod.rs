@@ -1 +1,3 @@
-pub mod dummy;
\ No newline at end of file
+pub mod codex;
+pub mod dummy;
+pub mod fixtures;
\ No newline at end of file